@@ -0,0 +1,65 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::io::{self, Write};
+
+use {Entry, format_iso_date};
+
+/// Write the entries as CSV (RFC 4180), with the header `date,time,duration_minutes,message`.
+/// The date is written as `YYYY-MM-DD` and is empty for recurring entries, the time is empty for all-day entries and
+/// the duration is empty for entries without one.
+pub fn to_csv<W: Write>(entries: &[Entry], mut writer: W) -> io::Result<()> {
+    write!(writer, "date,time,duration_minutes,message\r\n")?;
+    for entry in entries {
+        if let Some(date) = entry.date {
+            write!(writer, "{}", format_iso_date(&date))?;
+        }
+        write!(writer, ",")?;
+        if let Some(time) = entry.time {
+            write!(writer, "{}", time)?;
+        }
+        write!(writer, ",")?;
+        if let Some(duration) = entry.duration {
+            write!(writer, "{}", duration.as_secs() / 60)?;
+        }
+        write!(writer, ",\"{}\"\r\n", entry.msg.replace('"', "\"\""))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+    use super::to_csv;
+
+    #[test]
+    fn export_csv() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event \"name\", with comma
+        REM Apr 9 2018 MSG All day";
+        let entries = parse_str(data).expect("entries");
+        let mut csv = vec![];
+        to_csv(&entries, &mut csv).expect("csv");
+        assert_eq!(String::from_utf8(csv).expect("string"), "date,time,duration_minutes,message\r
+2018-03-30,19:00,75,\"Event \"\"name\"\", with comma\"\r
+2018-04-09,,,\"All day\"\r
+");
+    }
+}
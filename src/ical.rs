@@ -0,0 +1,172 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use {Date, Entry, Time};
+
+/// The day number of January 1, 1970.
+const EPOCH_DAY_NUMBER: i64 = 719_468;
+const MAX_LINE_OCTETS: usize = 75;
+const MIDNIGHT: Time = Time { hour: 0, minute: 0, second: 0 };
+
+/// Export the entries as an iCalendar (RFC 5545) calendar with one event per entry.
+/// The times are written as floating local times since entries have no time zone.
+/// Recurring entries are skipped: use `expand` to get their occurrences.
+pub fn to_ical(entries: &[Entry]) -> String {
+    let mut output = String::new();
+    write_line(&mut output, "BEGIN:VCALENDAR");
+    write_line(&mut output, "VERSION:2.0");
+    write_line(&mut output, "PRODID:-//rem-rs//rem//EN");
+    let stamp = utc_now();
+    for entry in entries {
+        let date =
+            match entry.date {
+                Some(date) => date,
+                None => continue,
+            };
+        write_line(&mut output, "BEGIN:VEVENT");
+        write_line(&mut output, &format!("UID:{:016x}@rem", hash(&entry.to_string())));
+        write_line(&mut output, &format!("DTSTAMP:{}T{}Z", format_date(&stamp.0), format_time(&stamp.1)));
+        match (entry.time, entry.end()) {
+            (Some(time), Some((end_date, end_time))) => {
+                write_line(&mut output, &format!("DTSTART:{}T{}", format_date(&date), format_time(&time)));
+                write_line(&mut output, &format!("DTEND:{}T{}", format_date(&end_date), format_time(&end_time)));
+            },
+            // An all-day entry ending at a later midnight, including one without duration, lasts for whole days.
+            (None, Some((end_date, end_time))) if end_date > date && end_time == MIDNIGHT => {
+                write_line(&mut output, &format!("DTSTART;VALUE=DATE:{}", format_date(&date)));
+                write_line(&mut output, &format!("DTEND;VALUE=DATE:{}", format_date(&end_date)));
+            },
+            (None, Some((end_date, end_time))) if (end_date, end_time) > (date, MIDNIGHT) => {
+                write_line(&mut output, &format!("DTSTART:{}T{}", format_date(&date), format_time(&MIDNIGHT)));
+                write_line(&mut output, &format!("DTEND:{}T{}", format_date(&end_date), format_time(&end_time)));
+            },
+            // An all-day entry with an empty duration or ending after the year 65535 only gets its start.
+            _ => write_line(&mut output, &format!("DTSTART;VALUE=DATE:{}", format_date(&date))),
+        }
+        write_line(&mut output, &format!("SUMMARY:{}", escape(&entry.msg)));
+        write_line(&mut output, "END:VEVENT");
+    }
+    write_line(&mut output, "END:VCALENDAR");
+    output
+}
+
+/// Escape the special characters of a text value.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' | ';' | ',' => {
+                result.push('\\');
+                result.push(char);
+            },
+            '\n' => result.push_str("\\n"),
+            _ => result.push(char),
+        }
+    }
+    result
+}
+
+fn format_date(date: &Date) -> String {
+    format!("{:04}{:02}{:02}", date.year, date.month.as_index() + 1, date.day)
+}
+
+fn format_time(time: &Time) -> String {
+    format!("{:02}{:02}{:02}", time.hour, time.minute, time.second)
+}
+
+/// 64-bit FNV-1a hash, used instead of the standard hasher because the UIDs must stay the same across
+/// Rust versions.
+fn hash(text: &str) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn utc_now() -> (Date, Time) {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let time = Time {
+        hour: (seconds / 60 / 60 % 24) as u8,
+        minute: (seconds / 60 % 60) as u8,
+        second: (seconds % 60) as u8,
+    };
+    let date = Date::from_day_number((seconds / (24 * 60 * 60)) as i64 + EPOCH_DAY_NUMBER).unwrap_or(Date::MAX);
+    (date, time)
+}
+
+/// Write a content line, folding it so that no line is longer than 75 octets.
+fn write_line(output: &mut String, line: &str) {
+    let mut length = 0;
+    for char in line.chars() {
+        if length + char.len_utf8() > MAX_LINE_OCTETS {
+            output.push_str("\r\n ");
+            // The space at the start of the continuation line counts toward its length.
+            length = 1;
+        }
+        output.push(char);
+        length += char.len_utf8();
+    }
+    output.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+    use super::{hash, to_ical};
+
+    #[test]
+    fn export_ical() {
+        let data = "REM Mar 30 2018 AT 23:30 DURATION 1:00 MSG Event name, with; special\\characters
+        REM Apr 9 2018 MSG All day
+        REM Apr 10 2018 DURATION 48:00 MSG Trip
+        REM Apr 20 2018 DURATION 5:00 MSG Morning";
+        let entries = parse_str(data).expect("entries");
+        let ical = to_ical(&entries);
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ical.matches("BEGIN:VEVENT\r\n").count(), 4);
+        assert!(ical.contains("\r\nDTSTART:20180330T233000\r\nDTEND:20180331T003000\r\n"));
+        assert!(ical.contains("\r\nSUMMARY:Event name\\, with\\; special\\\\characters\r\n"));
+        assert!(ical.contains("\r\nDTSTART;VALUE=DATE:20180409\r\nDTEND;VALUE=DATE:20180410\r\n"));
+        assert!(ical.contains("\r\nDTSTART;VALUE=DATE:20180410\r\nDTEND;VALUE=DATE:20180412\r\n"));
+        assert!(ical.contains("\r\nDTSTART:20180420T000000\r\nDTEND:20180420T050000\r\n"));
+        assert!(ical.contains(&format!("\r\nUID:{:016x}@rem\r\n", hash(&entries[0].to_string()))));
+        assert_eq!(to_ical(&entries).lines().find(|line| line.starts_with("UID")),
+            ical.lines().find(|line| line.starts_with("UID")));
+    }
+
+    #[test]
+    fn fold_long_lines() {
+        let message = "à".repeat(100);
+        let entries = parse_str(&format!("REM Mar 30 2018 MSG {}", message)).expect("entries");
+        let ical = to_ical(&entries);
+        for line in ical.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        assert!(ical.replace("\r\n ", "").contains(&format!("SUMMARY:{}\r\n", message)));
+    }
+}
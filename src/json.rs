@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::fmt::Write;
+
+use {Entry, format_iso_date};
+
+/// Export the entries as a JSON array of objects with the fields `date`, `time`, `durationMinutes` and `message`.
+/// The date is written as `YYYY-MM-DD` and is `null` for recurring entries, the time is `null` for all-day entries and
+/// the duration is `null` for entries without one.
+pub fn to_json(entries: &[Entry]) -> String {
+    let mut output = String::from("[");
+    for (index, entry) in entries.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        output.push_str("{\"date\":");
+        match entry.date {
+            Some(date) => push_string(&mut output, &format_iso_date(&date)),
+            None => output.push_str("null"),
+        }
+        output.push_str(",\"time\":");
+        match entry.time {
+            Some(time) => push_string(&mut output, &time.to_string()),
+            None => output.push_str("null"),
+        }
+        output.push_str(",\"durationMinutes\":");
+        match entry.duration {
+            Some(duration) => output.push_str(&(duration.as_secs() / 60).to_string()),
+            None => output.push_str("null"),
+        }
+        output.push_str(",\"message\":");
+        push_string(&mut output, &entry.msg);
+        output.push('}');
+    }
+    output.push(']');
+    output
+}
+
+/// Append `string` as a JSON string literal, escaping the quotes, the backslashes and the control characters.
+fn push_string(output: &mut String, string: &str) {
+    output.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            char if char.is_control() => {
+                // Writing to a string cannot fail.
+                let _ = write!(output, "\\u{:04x}", char as u32);
+            },
+            char => output.push(char),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+    use super::to_json;
+
+    #[test]
+    fn export_json() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event \"name\"\t\\ here
+        REM Apr 9 2018 MSG All day";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(to_json(&entries), concat!(
+            "[{\"date\":\"2018-03-30\",\"time\":\"19:00\",\"durationMinutes\":75,",
+            "\"message\":\"Event \\\"name\\\"\\t\\\\ here\"},",
+            "{\"date\":\"2018-04-09\",\"time\":null,\"durationMinutes\":null,\"message\":\"All day\"}]",
+        ));
+    }
+}
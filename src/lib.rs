@@ -19,14 +19,79 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+
+mod csv;
+mod ical;
+mod json;
+mod markdown;
+mod query;
+mod schedule;
+mod stats;
+mod table;
+
 use std::cmp::Ordering;
-use std::io::{BufRead, BufReader, Read};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+#[cfg(feature = "tokio")]
+use std::future::{self, Future};
+use std::io::{self, BufRead, BufReader, Lines, Read};
+#[cfg(feature = "tokio")]
+use std::mem;
 use std::num::ParseIntError;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::OnceLock;
+#[cfg(feature = "tokio")]
+use std::task::Poll;
 use std::time::Duration;
 
+#[cfg(feature = "chrono")]
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncBufReadExt, AsyncRead};
+
+pub use csv::to_csv;
+pub use ical::to_ical;
+pub use json::to_json;
+pub use markdown::to_markdown;
+pub use schedule::Schedule;
+pub use stats::{Stats, stats};
+pub use table::format_table;
+pub use query::{
+    dedup,
+    entries_in_range,
+    entries_on,
+    entries_with_tag,
+    expand,
+    find_overlaps,
+    gaps_on,
+    group_by_month,
+    is_active_on,
+    next_after,
+    search,
+    sort_by_priority,
+    total_duration,
+    total_duration_on,
+};
 use Month::*;
+use Weekday::*;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Month {
     January = 0,
     February = 1,
@@ -42,228 +107,3515 @@ pub enum Month {
     December = 11,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+const ENGLISH_MONTH_NAMES: [&str; 12] = ["january", "february", "march", "april", "may", "june", "july", "august",
+    "september", "october", "november", "december"];
+
+const FRENCH_MONTH_NAMES: [&str; 12] = ["janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août",
+    "septembre", "octobre", "novembre", "décembre"];
+
+const MONTHS: [Month; 12] = [January, February, March, April, May, June, July, August, September, October,
+    November, December];
+
+impl Month {
+    /// Get the index of the month, from 0 for January to 11 for December.
+    pub fn as_index(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Get the full English name of the month, like `March`, while `Display` writes its abbreviation.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            January => "January",
+            February => "February",
+            March => "March",
+            April => "April",
+            May => "May",
+            June => "June",
+            July => "July",
+            August => "August",
+            September => "September",
+            October => "October",
+            November => "November",
+            December => "December",
+        }
+    }
+}
+
+impl TryFrom<u8> for Month {
+    type Error = ErrorKind;
+
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
+        MONTHS.get(index as usize)
+            .cloned()
+            .ok_or_else(|| ErrorKind::InvalidMonth(index.to_string()))
+    }
+}
+
+impl FromStr for Month {
+    type Err = ErrorKind;
+
+    /// Parse a month written as the start of its name, of at least three letters, ignoring case, like `Sep`, `sept`
+    /// or `September`.
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        parse_month_prefix(string, &ENGLISH_MONTH_NAMES)
+    }
+}
+
+impl Display for Month {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let abbreviation = match *self {
+            January => "Jan",
+            February => "Feb",
+            March => "Mar",
+            April => "Apr",
+            May => "May",
+            June => "Jun",
+            July => "Jul",
+            August => "Aug",
+            September => "Sep",
+            October => "Oct",
+            November => "Nov",
+            December => "Dec",
+        };
+        write!(formatter, "{}", abbreviation)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Display for Weekday {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let abbreviation = match *self {
+            Monday => "Mon",
+            Tuesday => "Tue",
+            Wednesday => "Wed",
+            Thursday => "Thu",
+            Friday => "Fri",
+            Saturday => "Sat",
+            Sunday => "Sun",
+        };
+        write!(formatter, "{}", abbreviation)
+    }
+}
+
+/// Parse the abbreviation or the full name of a day of the week, ignoring case.
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    let weekday = match word.to_lowercase().as_str() {
+        "mon" | "monday" => Monday,
+        "tue" | "tuesday" => Tuesday,
+        "wed" | "wednesday" => Wednesday,
+        "thu" | "thursday" => Thursday,
+        "fri" | "friday" => Friday,
+        "sat" | "saturday" => Saturday,
+        "sun" | "sunday" => Sunday,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+/// Get the number of days of `month` in `year`.
+pub fn days_in_month(month: &Month, year: u16) -> u8 {
+    match *month {
+        February if is_leap_year(year) => 29,
+        February => 28,
+        April | June | September | November => 30,
+        _ => 31,
+    }
+}
+
+/// Check whether `year` has a February 29 in the Gregorian calendar.
+pub fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+// The fields are declared from the most significant to the least significant so that the derived ordering is
+// chronological.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Date {
-    pub day: u8,
-    pub month: Month,
     pub year: u16,
+    pub month: Month,
+    pub day: u8,
 }
 
-impl PartialOrd for Date {
-    fn partial_cmp(&self, other: &Date) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Date {
+    /// The earliest date whose year fits in a `u16`.
+    pub const MIN: Date = Date { year: 0, month: January, day: 1 };
+    /// The latest date whose year fits in a `u16`.
+    pub const MAX: Date = Date { year: u16::MAX, month: December, day: 31 };
+
+    /// Create a date, checking that the day exists in this month and year.
+    pub fn new(day: u8, month: Month, year: u16) -> Result<Date, ErrorKind> {
+        let date = Date {
+            day,
+            month,
+            year,
+        };
+        date.validate()?;
+        Ok(date)
     }
-}
 
-impl Ord for Date {
-    fn cmp(&self, other: &Date) -> Ordering {
-        if self.year < other.year || (self.year == other.year && self.month < other.month || (self.month == other.month
-            && self.day < other.day)) {
-            Ordering::Less
-        }
-        else if self == other {
-            Ordering::Equal
-        }
-        else {
-            Ordering::Greater
+    /// Check that the day exists in this month and year.
+    pub fn validate(&self) -> Result<(), ErrorKind> {
+        if self.day < 1 || self.day > days_in_month(&self.month, self.year) {
+            return Err(ErrorKind::InvalidDate(*self));
         }
+        Ok(())
+    }
+
+    /// Get the day of the week.
+    pub fn weekday(&self) -> Weekday {
+        // The day number 0, March 1 of the year 0, is a Wednesday.
+        [Wednesday, Thursday, Friday, Saturday, Sunday, Monday, Tuesday][self.day_number().rem_euclid(7) as usize]
+    }
+
+    /// Get the day of the year, from 1 for January 1 to 365, or 366 in a leap year, for December 31.
+    pub fn ordinal(&self) -> u16 {
+        let preceding_days: u16 = MONTHS[..self.month.as_index() as usize].iter()
+            .map(|month| days_in_month(month, self.year) as u16)
+            .sum();
+        preceding_days + self.day as u16
+    }
+
+    /// Check whether the date is a Saturday or a Sunday.
+    pub fn is_weekend(&self) -> bool {
+        matches!(self.weekday(), Saturday | Sunday)
+    }
+
+    /// Check whether the date is between Monday and Friday.
+    pub fn is_weekday(&self) -> bool {
+        !self.is_weekend()
+    }
+
+    /// Get the date `days` days later, or earlier if `days` is negative, or `None` if its year does not fit in a
+    /// `u16`.
+    pub fn add_days(&self, days: i32) -> Option<Date> {
+        Date::from_day_number(self.day_number() + days as i64)
+    }
+
+    /// Get the previous day, or `None` for the first day of the year 0.
+    pub fn checked_pred(&self) -> Option<Date> {
+        Date::from_day_number(self.day_number() - 1)
+    }
+
+    /// Get the number of days since March 1 of the year 0, like in the algorithms of Howard Hinnant, which start the
+    /// year in March so that the leap day is its last day.
+    fn day_number(&self) -> i64 {
+        let (year, month) =
+            if self.month < March {
+                (self.year as i64 - 1, self.month.as_index() as i64 + 10)
+            }
+            else {
+                (self.year as i64, self.month.as_index() as i64 - 2)
+            };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era
+    }
+
+    /// Get the date from its number of days since March 1 of the year 0, or `None` if its year does not fit in a
+    /// `u16`.
+    fn from_day_number(number: i64) -> Option<Date> {
+        let era = number.div_euclid(146_097);
+        let day_of_era = number - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // The month starting from March.
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let (month, year) =
+            if month < 10 {
+                (month + 2, year_of_era + era * 400)
+            }
+            else {
+                (month - 10, year_of_era + era * 400 + 1)
+            };
+        Some(Date {
+            day: day as u8,
+            month: MONTHS[month as usize],
+            year: u16::try_from(year).ok()?,
+        })
+    }
+
+    /// Get the next day, or `None` for the last day of the year 65535.
+    pub fn checked_succ(&self) -> Option<Date> {
+        Date::from_day_number(self.day_number() + 1)
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg(feature = "chrono")]
+impl Date {
+    /// Convert to a `chrono` date, returning `None` if the date is invalid.
+    pub fn to_naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year as i32, self.month.as_index() as u32 + 1, self.day as u32)
+    }
+
+    /// Convert from a `chrono` date, returning `None` if its year does not fit in a `u16`.
+    pub fn from_naive_date(date: &NaiveDate) -> Option<Self> {
+        Some(Date {
+            year: u16::try_from(date.year()).ok()?,
+            month: MONTHS[date.month0() as usize],
+            day: date.day() as u8,
+        })
+    }
+}
+
+/// The year is written with at least four digits so that it is not expanded when parsed back.
+impl Display for Date {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{} {} {:04}", self.month, self.day, self.year)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Time {
     pub hour: u8,
     pub minute: u8,
+    pub second: u8,
 }
 
-impl PartialOrd for Time {
-    fn partial_cmp(&self, other: &Time) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Time {
+    /// Create the time at `minutes` after midnight, wrapping to the start of the day after 23:59.
+    pub fn from_minutes_since_midnight(minutes: u16) -> Self {
+        let minutes = minutes % (24 * 60);
+        Time {
+            hour: (minutes / 60) as u8,
+            minute: (minutes % 60) as u8,
+            second: 0,
+        }
+    }
+
+    /// Get the number of whole minutes since midnight, ignoring the seconds.
+    pub fn minutes_since_midnight(&self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+
+    /// Check that the time exists in a day.
+    pub fn validate(&self) -> Result<(), ErrorKind> {
+        if self.hour > 23 {
+            return Err(ErrorKind::InvalidHour(self.hour));
+        }
+        if self.minute > 59 {
+            return Err(ErrorKind::InvalidMinute(self.minute));
+        }
+        if self.second > 59 {
+            return Err(ErrorKind::InvalidSecond(self.second));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Time {
+    /// Convert to a `chrono` time, returning `None` if the time is invalid.
+    pub fn to_naive_time(&self) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(self.hour as u32, self.minute as u32, self.second as u32)
+    }
+
+    pub fn from_naive_time(time: &NaiveTime) -> Self {
+        Time {
+            hour: time.hour() as u8,
+            minute: time.minute() as u8,
+            second: time.second() as u8,
+        }
     }
 }
 
-impl Ord for Time {
-    fn cmp(&self, other: &Time) -> Ordering {
-        if self.hour < other.hour || (self.hour == other.hour && self.minute < other.minute) {
-            Ordering::Less
+/// The seconds are only shown when they are not zero.
+impl Display for Time {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:02}:{:02}", self.hour, self.minute)?;
+        if self.second != 0 {
+            write!(formatter, ":{:02}", self.second)?;
         }
-        else if self == other {
-            Ordering::Equal
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    AmbiguousMonth(String),
+    DuplicateKeyword(String),
+    DurationCrossesMidnight,
+    ExpectedKeyword {
+        expected: String,
+        found: Option<String>,
+    },
+    InvalidDate(Date),
+    InvalidDay(u8),
+    InvalidDuration(String),
+    InvalidHour(u8),
+    /// An hour of a time written with `am` or `pm`.
+    InvalidHour12(u8),
+    InvalidMinute(u8),
+    InvalidMonth(String),
+    InvalidNumber(ParseIntError),
+    InvalidPriority(u16),
+    InvalidSecond(u8),
+    InvalidSkipRule(String),
+    InvalidTime(String),
+    Io(io::Error),
+    LineTooLong(usize),
+    NegativeDuration(String),
+    UnexpectedEndOfLine,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            ErrorKind::AmbiguousMonth(ref month) => write!(formatter, "Ambiguous month {}", month),
+            ErrorKind::DuplicateKeyword(ref keyword) => write!(formatter, "Duplicate keyword {}", keyword),
+            ErrorKind::DurationCrossesMidnight => write!(formatter, "Duration continues past midnight"),
+            ErrorKind::ExpectedKeyword { ref expected, found: Some(ref found) } =>
+                write!(formatter, "Expecting {}, found {}", expected, found),
+            ErrorKind::ExpectedKeyword { ref expected, found: None } =>
+                write!(formatter, "Expecting {}, found end of line", expected),
+            ErrorKind::InvalidDate(ref date) => write!(formatter, "Invalid date {}", date),
+            ErrorKind::InvalidDay(day) => write!(formatter, "Invalid day {}, expecting 1 to 31", day),
+            ErrorKind::InvalidDuration(ref duration) => write!(formatter, "Invalid duration {}", duration),
+            ErrorKind::InvalidHour(24) => write!(formatter, "Invalid hour 24, use 00:00 for midnight"),
+            ErrorKind::InvalidHour(hour) => write!(formatter, "Invalid hour {}, expecting 0 to 23", hour),
+            ErrorKind::InvalidHour12(hour) => write!(formatter, "Invalid hour {}, expecting 1 to 12", hour),
+            ErrorKind::InvalidMinute(minute) => write!(formatter, "Invalid minute {}, expecting 0 to 59", minute),
+            ErrorKind::InvalidMonth(ref month) => write!(formatter, "Invalid month {}", month),
+            ErrorKind::InvalidNumber(ref error) => write!(formatter, "Invalid number: {}", error),
+            ErrorKind::InvalidPriority(priority) =>
+                write!(formatter, "Invalid priority {}, expecting 0 to {}", priority, MAX_PRIORITY),
+            ErrorKind::InvalidSecond(second) => write!(formatter, "Invalid second {}, expecting 0 to 59", second),
+            ErrorKind::InvalidSkipRule(ref rule) =>
+                write!(formatter, "Invalid skip rule {}, expecting WEEKEND or NONE", rule),
+            ErrorKind::InvalidTime(ref time) => write!(formatter, "Invalid time {}", time),
+            ErrorKind::Io(ref error) => write!(formatter, "{}", error),
+            ErrorKind::LineTooLong(length) => write!(formatter, "Line too long ({} bytes)", length),
+            ErrorKind::NegativeDuration(ref duration) =>
+                write!(formatter, "Negative duration {}, a duration cannot be less than 0:00", duration),
+            ErrorKind::UnexpectedEndOfLine => write!(formatter, "Unexpected end of line"),
         }
-        else {
-            Ordering::Greater
+    }
+}
+
+impl error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ErrorKind::InvalidNumber(ref error) => Some(error),
+            ErrorKind::Io(ref error) => Some(error),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Entry {
-    pub date: Date,
-    pub duration: Duration,
-    pub msg: String,
-    pub time: Time,
+/// An error that occurred while parsing the (1-based) line `line`.
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub line: usize,
+    /// The (0-based) index of the reader in which the error occurred, when parsing several with `parse_many`.
+    pub reader: Option<usize>,
+    /// The byte range of the offending part of the line, if known.
+    pub span: Option<Range<usize>>,
 }
 
-pub fn parse<R: Read>(reader: R) -> Result<Vec<Entry>, String> {
-    let mut entries = vec![];
-    let reader = BufReader::new(reader);
-    for line in reader.lines() {
-        let line = line.map_err(|error| error.to_string())?;
-        let mut parser = Parser::new(&line);
-        if let Ok(entry) = parser.entry() {
-            entries.push(entry);
+impl ParseError {
+    pub fn new(kind: ErrorKind, line: usize) -> Self {
+        Self {
+            kind,
+            line,
+            reader: None,
+            span: None,
         }
     }
-    Ok(entries)
+
+    /// Show the error under the line it occurred on, with carets pointing to the offending part:
+    ///
+    /// ```text
+    /// REM Foo 30 2018 MSG Event
+    ///     ^^^ Invalid month foo
+    /// ```
+    pub fn render(&self, line: &str) -> String {
+        let mut result = format!("{}\n", line);
+        if let Some(ref span) = self.span {
+            let start = span.start.min(line.len());
+            let end = span.end.clamp(start, line.len());
+            if let (Some(prefix), Some(underlined)) = (line.get(..start), line.get(start..end)) {
+                // Keep the tabs so that the carets are aligned with the line.
+                for char in prefix.chars() {
+                    result.push(if char == '\t' { '\t' } else { ' ' });
+                }
+                result.push_str(&"^".repeat(underlined.chars().count().max(1)));
+                result.push(' ');
+            }
+        }
+        result.push_str(&self.kind.to_string());
+        result
+    }
 }
 
-struct Parser {
-    index: usize,
-    words: Vec<String>,
+impl Display for ParseError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        if let Some(reader) = self.reader {
+            write!(formatter, "reader {}, ", reader)?;
+        }
+        write!(formatter, "line {}: {}", self.line, self.kind)
+    }
 }
 
-impl Parser {
-    fn new(line: &str) -> Self {
-        let words = line.split_whitespace()
-            .filter(|word| !word.trim().is_empty())
-            .map(ToString::to_string)
-            .collect();
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.kind.source()
+    }
+}
+
+/// The byte-order mark that some editors write at the start of UTF-8 files.
+const BOM: char = '\u{feff}';
+
+/// The approximate size of an entry in a file, used to estimate the number of entries from the size of a file.
+const BYTES_PER_ENTRY: usize = 40;
+
+/// The maximum number of entries reserved from the size of a file, since a large file may hold few entries, e.g. when
+/// it is mostly comments.
+const MAX_RESERVED_ENTRIES: usize = 4096;
+
+/// An iterator that reads and parses the entries one line at a time.
+/// Blank lines and comments are skipped, and the iteration ends after an I/O error.
+pub struct EntryReader<R> {
+    done: bool,
+    joiner: LineJoiner,
+    line_number: usize,
+    lines: Lines<BufReader<R>>,
+    options: ParseOptions,
+}
+
+impl<R: Read> EntryReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, ParseOptions::default())
+    }
+
+    pub fn with_keywords(reader: R, keywords: Keywords) -> Self {
+        Self::with_locale(reader, keywords, Locale::default())
+    }
+
+    pub fn with_locale(reader: R, keywords: Keywords, locale: Locale) -> Self {
+        Self::with_options(reader, ParseOptions::default().keywords(keywords).locale(locale))
+    }
+
+    pub fn with_options(reader: R, options: ParseOptions) -> Self {
         Self {
-            index: 0,
-            words,
-        }
-    }
-
-    fn date(&mut self) -> Result<Date, String> {
-        let month =
-            match self.next_word().ok_or_else(|| "Expecting date, found end of line".to_string())?.to_lowercase().as_str() {
-                "jan" => January,
-                "feb" => February,
-                "mar" => March,
-                "apr" => April,
-                "may" => May,
-                "jun" => June,
-                "jul" => July,
-                "aug" => August,
-                "sep" => September,
-                "oct" => October,
-                "nov" => November,
-                "dec" => December,
-                month => return Err(format!("Invalid month {}", month)),
+            done: false,
+            joiner: LineJoiner::default(),
+            line_number: 0,
+            lines: BufReader::new(reader).lines(),
+            options,
+        }
+    }
+}
+
+impl<R: Read> Iterator for EntryReader<R> {
+    type Item = Result<Entry, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let line = self.lines.next();
+            let joined =
+                match line {
+                    Some(Ok(line)) => {
+                        self.line_number += 1;
+                        self.joiner.push(line, self.line_number, 0..0, &self.options.lowercase_keywords)
+                    },
+                    Some(Err(error)) => {
+                        self.line_number += 1;
+                        self.done = true;
+                        return Some(Err(ParseError::new(ErrorKind::Io(error), self.line_number)));
+                    },
+                    None => {
+                        self.done = true;
+                        self.joiner.finish()
+                    },
+                };
+            if let Some(joined) = joined {
+                if let Some(result) = parse_line(&joined.text, joined.first_line, &self.options) {
+                    return Some(result);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A line joined with the lines on which its message continues.
+struct JoinedLine {
+    /// The number of the first line.
+    first_line: usize,
+    /// The byte range of the lines in the input, without the terminator of the last line.
+    span: Range<usize>,
+    text: String,
+}
+
+/// Join the lines on which the messages continue, the lines being given one at a time.
+#[derive(Default)]
+struct LineJoiner {
+    continued: Option<JoinedLine>,
+}
+
+impl LineJoiner {
+    /// Add `line`, whose number is `number` and whose byte range in the input is `span`, returning the joined
+    /// line unless its message continues on the next line. The callers that do not need the span give an empty one.
+    fn push(&mut self, line: String, number: usize, span: Range<usize>, keywords: &Keywords) -> Option<JoinedLine> {
+        let mut joined =
+            match self.continued.take() {
+                Some(mut joined) => {
+                    joined.text.push_str(line.trim_start());
+                    joined.span.end = span.end;
+                    joined
+                },
+                None => JoinedLine {
+                    first_line: number,
+                    span,
+                    text: line,
+                },
             };
-        let day = self.num()? as u8;
-        let year = self.num()? as u16;
-        Ok(Date {
-            day,
-            month,
-            year,
-        })
+        match continuation(&joined.text, keywords) {
+            Some(length) => {
+                joined.text.truncate(length);
+                joined.text.push(' ');
+                self.continued = Some(joined);
+                None
+            },
+            None => Some(joined),
+        }
     }
 
-    fn duration(&mut self) -> Result<Duration, String> {
-        self.ident("DURATION")?;
-        let time = self.time_num()?;
-        Ok(Duration::from_secs(time.hour as u64 * 60 * 60 + time.minute as u64 * 60))
+    /// Get the line whose message was still continued at the end of the input.
+    fn finish(&mut self) -> Option<JoinedLine> {
+        self.continued.take()
     }
+}
 
-    fn entry(&mut self) -> Result<Entry, String> {
-        self.ident("REM")?;
-        let date = self.date()?;
-        let time = self.time()?;
-        let duration = self.duration()?;
-        let msg = self.message()?;
-        Ok(Entry {
-            date,
-            duration,
-            msg,
-            time,
+/// An error that occurred while reading a file with `parse_file`.
+#[derive(Debug)]
+pub enum FileError {
+    Open {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        error: ParseError,
+    },
+}
+
+impl Display for FileError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            FileError::Open { ref path, ref error } =>
+                write!(formatter, "Cannot open {}: {}", path.display(), error),
+            FileError::Parse { ref path, ref error } => write!(formatter, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl error::Error for FileError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FileError::Open { ref error, .. } => Some(error),
+            FileError::Parse { ref error, .. } => Some(error),
+        }
+    }
+}
+
+const MAX_PRIORITY: u16 = 9999;
+
+/// The rule by which an entry repeats.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Recurrence {
+    /// Every month on the same day, or on the last day of the month for the months that are too short.
+    Monthly {
+        day: u8,
+    },
+    /// Every week on the same day.
+    Weekly(Weekday),
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            Recurrence::Monthly { day } => write!(formatter, "* {}", day),
+            Recurrence::Weekly(weekday) => write!(formatter, "{}", weekday),
+        }
+    }
+}
+
+/// What to do with an occurrence of an entry falling on some days.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum SkipRule {
+    /// Keep the occurrence on its day.
+    #[default]
+    None,
+    /// Move an occurrence on a Saturday or a Sunday to the following Monday.
+    Weekend,
+}
+
+#[derive(Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Entry {
+    /// The number of days before the entry from which to remind it.
+    pub advance_days: u8,
+    /// The date of the entry, or `None` for a recurring entry.
+    pub date: Option<Date>,
+    pub duration: Option<Duration>,
+    pub msg: String,
+    /// The dates on which a recurring entry does not occur.
+    pub omit: Vec<Date>,
+    /// The importance of the entry, higher being more important.
+    pub priority: Option<u16>,
+    pub recurrence: Option<Recurrence>,
+    /// The days from which the occurrences are moved, applied by `expand`.
+    pub skip: SkipRule,
+    pub tags: Vec<String>,
+    /// The start time of the entry, or `None` for an all-day entry.
+    pub time: Option<Time>,
+    /// The last date on which a recurring entry occurs.
+    pub until: Option<Date>,
+}
+
+impl Entry {
+    /// Get the duration as hours, minutes and seconds, like it is written after `DURATION`, or `None` if it lasts
+    /// too many hours to fit in a `Time`.
+    /// An entry without duration lasts `0:00`.
+    pub fn duration_as_time(&self) -> Option<Time> {
+        let seconds = self.duration.unwrap_or_default().as_secs();
+        Some(Time {
+            hour: u8::try_from(seconds / 60 / 60).ok()?,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
         })
     }
 
-    fn ident(&mut self, ident: &str) -> Result<(), String> {
-        if self.next_word().map(str::to_lowercase) != Some(ident.to_lowercase()) {
-            return Err("Expecting REM at beginning of line".to_string());
+    /// Get the date and time at which the entry ends, which may be on a later day than its start, or `None` for a
+    /// recurring entry or an entry ending after the year 65535.
+    /// An all-day entry is considered to start at midnight and to last for the whole day without duration, while a
+    /// timed entry without duration ends when it starts.
+    pub fn end(&self) -> Option<(Date, Time)> {
+        let (date, start) = self.start()?;
+        let duration =
+            match (self.time, self.duration) {
+                (None, None) => Duration::from_secs(SECONDS_PER_DAY),
+                (_, duration) => duration.unwrap_or_default(),
+            };
+        let seconds = (start.hour as u64 * 60 * 60 + start.minute as u64 * 60 + start.second as u64)
+            .checked_add(duration.as_secs())?;
+        let days = i64::try_from(seconds / SECONDS_PER_DAY).ok()?;
+        let date = Date::from_day_number(date.day_number().checked_add(days)?)?;
+        let seconds = seconds % SECONDS_PER_DAY;
+        let time = Time {
+            hour: (seconds / 60 / 60) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+        };
+        Some((date, time))
+    }
+
+    /// Check whether `time` on the date of the entry is between its start, included, and its end, excluded, the
+    /// end of an entry continuing past midnight being the end of the day. An all-day entry without duration contains
+    /// every time of its day, while a timed entry without duration and a recurring entry contain no time.
+    pub fn contains_time(&self, time: &Time) -> bool {
+        match (self.start(), self.end()) {
+            (Some((date, start)), Some((end_date, end))) => *time >= start && (end_date > date || *time < end),
+            _ => false,
         }
-        Ok(())
     }
 
-    fn message(&mut self) -> Result<String, String> {
-        self.ident("MSG")?;
-        let message = self.words[self.index..].join(" ");
-        Ok(message)
+    /// Check whether the entry lasts all day, i.e. it has no `AT` clause.
+    pub fn is_all_day(&self) -> bool {
+        self.time.is_none()
     }
 
-    fn next_word(&mut self) -> Option<&str> {
-        let index = self.index;
-        let result = self.words.get(index)
-            .map(|string| string.as_str());
-        if result.is_some() {
-            self.index += 1;
+    /// Check whether both entries occupy the same slot, i.e. they have the same date, or recurrence, time and
+    /// duration, whatever their message and other clauses are.
+    pub fn same_slot(&self, other: &Entry) -> bool {
+        self.date == other.date && self.recurrence == other.recurrence && self.time == other.time
+            && self.duration == other.duration
+    }
+
+    /// Get the date and time at which the entry starts, using midnight for an all-day entry.
+    fn start(&self) -> Option<(Date, Time)> {
+        Some((self.date?, self.time.unwrap_or(Time { hour: 0, minute: 0, second: 0 })))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Entry {
+    /// Create an entry without duration starting at the `chrono` date and time, returning `None` if its year does
+    /// not fit in a `u16`.
+    pub fn from_chrono(datetime: &NaiveDateTime, msg: &str) -> Option<Self> {
+        Some(Entry {
+            advance_days: 0,
+            date: Some(Date::from_naive_date(&datetime.date())?),
+            duration: None,
+            msg: msg.to_string(),
+            omit: vec![],
+            priority: None,
+            recurrence: None,
+            skip: SkipRule::None,
+            tags: vec![],
+            time: Some(Time::from_naive_time(&datetime.time())),
+            until: None,
+        })
+    }
+
+    /// Convert the start of the entry to a `chrono` date and time, returning `None` for a recurring entry or if the
+    /// date or the time is invalid. An all-day entry starts at midnight.
+    pub fn to_naive_datetime(&self) -> Option<NaiveDateTime> {
+        let time = match self.time {
+            Some(time) => time.to_naive_time()?,
+            None => NaiveTime::from_hms_opt(0, 0, 0)?,
+        };
+        Some(self.date?.to_naive_date()?.and_time(time))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Entries are ordered chronologically by their start, and then by their other fields to agree with equality.
+/// Recurring entries come first and all-day entries come before the timed entries of their day.
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        self.date.cmp(&other.date)
+            .then(self.time.cmp(&other.time))
+            .then_with(|| self.recurrence.cmp(&other.recurrence))
+            .then_with(|| self.duration.cmp(&other.duration))
+            .then_with(|| self.msg.cmp(&other.msg))
+            .then_with(|| self.priority.cmp(&other.priority))
+            .then_with(|| self.advance_days.cmp(&other.advance_days))
+            .then_with(|| self.omit.cmp(&other.omit))
+            .then_with(|| self.until.cmp(&other.until))
+            .then_with(|| self.tags.cmp(&other.tags))
+            .then_with(|| self.skip.cmp(&other.skip))
+    }
+}
+
+/// A builder to create an entry at a date and time, checking that they are valid.
+pub struct EntryBuilder {
+    date: Date,
+    duration: Option<Duration>,
+    msg: String,
+    time: Time,
+}
+
+impl EntryBuilder {
+    /// Start an entry on `date` at `time`, without duration and with an empty message.
+    pub fn new(date: Date, time: Time) -> Self {
+        Self {
+            date,
+            duration: None,
+            msg: String::new(),
+            time,
         }
-        result
     }
 
-    fn num(&mut self) -> Result<u32, String> {
-        self.next_word()
-            .ok_or_else(|| "Expecting day of month, found end of line".to_string())?
-            .parse()
-            .map_err(|error: ParseIntError| error.to_string())
-    }
-
-    fn time(&mut self) -> Result<Time, String> {
-        self.ident("AT")?;
-        let time = self.time_num()?;
-        Ok(time)
-    }
-
-    fn time_num(&mut self) -> Result<Time, String> {
-        let time = self.next_word().ok_or_else(|| "Expecting time, found end of line".to_string())?;
-        let mut parts = time.split(':');
-        let hour = parts.next()
-            .ok_or_else(|| "Expecting hour, found end of line".to_string())
-            .map_err(|error| error.to_string())?
-            .parse()
-            .map_err(|error: ParseIntError| error.to_string())?;
-        let minute = parts.next()
-            .ok_or_else(|| "Expecting hour, found end of line".to_string())
-            .map_err(|error| error.to_string())?
-            .parse()
-            .map_err(|error: ParseIntError| error.to_string())?;
-        Ok(Time {
-            hour,
-            minute,
+    pub fn build(self) -> Result<Entry, ErrorKind> {
+        self.date.validate()?;
+        self.time.validate()?;
+        Ok(Entry {
+            advance_days: 0,
+            date: Some(self.date),
+            duration: self.duration,
+            msg: self.msg,
+            omit: vec![],
+            priority: None,
+            recurrence: None,
+            skip: SkipRule::None,
+            tags: vec![],
+            time: Some(self.time),
+            until: None,
         })
     }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn message(mut self, msg: &str) -> Self {
+        self.msg = msg.to_string();
+        self
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::time::Duration;
+/// Parse a single line as an entry, errors being reported on line 1.
+impl FromStr for Entry {
+    type Err = ParseError;
 
-    use {Date, Time, parse};
-    use Month::*;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Parser::new(string, 1).entry()
+    }
+}
 
-    #[test]
-    fn parse_rem() {
-        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
-        let entries = parse(data.as_bytes()).expect("entries");
-        assert_eq!(entries[0].date, Date { day: 30, month: March, year: 2018 });
-        assert_eq!(entries[0].duration, Duration::from_secs(75 * 60));
-        assert_eq!(entries[0].msg, "Event name".to_string());
-        assert_eq!(entries[0].time, Time { hour: 19, minute: 0 });
+impl Display for Entry {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match (self.recurrence, self.date) {
+            (Some(recurrence), _) => write!(formatter, "REM {}", recurrence)?,
+            (None, Some(date)) => write!(formatter, "REM {}", date)?,
+            (None, None) => write!(formatter, "REM")?,
+        }
+        if self.advance_days != 0 {
+            write!(formatter, " +{}", self.advance_days)?;
+        }
+        if let Some(time) = self.time {
+            write!(formatter, " AT {}", time)?;
+        }
+        if let Some(duration) = self.duration {
+            write!(formatter, " DURATION {}", format_duration(&duration))?;
+        }
+        if let Some(until) = self.until {
+            write!(formatter, " UNTIL {}", until)?;
+        }
+        for date in &self.omit {
+            write!(formatter, " OMIT {}", date)?;
+        }
+        if self.skip == SkipRule::Weekend {
+            write!(formatter, " SKIP WEEKEND")?;
+        }
+        if let Some(priority) = self.priority {
+            write!(formatter, " PRIORITY {}", priority)?;
+        }
+        for tag in &self.tags {
+            write!(formatter, " TAG {}", tag)?;
+        }
+        // A message ending with a backslash is quoted so that it is not read back as continuing on the next line.
+        if self.msg.trim() != self.msg || self.msg.starts_with('"') || self.msg.ends_with('\\') {
+            write!(formatter, " MSG \"{}\"", self.msg.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        else if !self.msg.is_empty() {
+            write!(formatter, " MSG {}", self.msg)?;
+        }
+        Ok(())
+    }
+}
 
-        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
-        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event";
-        let entries = parse(data.as_bytes()).expect("entries");
-        assert_eq!(entries[0].date, Date { day: 30, month: March, year: 2018 });
-        assert_eq!(entries[0].duration, Duration::from_secs(75 * 60));
-        assert_eq!(entries[0].msg, "Event name".to_string());
-        assert_eq!(entries[0].time, Time { hour: 19, minute: 0 });
-        assert_eq!(entries[1].date, Date { day: 9, month: April, year: 2018 });
-        let _month = entries[1].date.month;
-        assert!(entries[1].date.month == April);
-        assert_eq!(entries[1].duration, Duration::from_secs(15 * 60));
-        assert_eq!(entries[1].msg, "Super Event".to_string());
-        assert_eq!(entries[1].time, Time { hour: 12, minute: 50 });
+pub fn parse<R: Read>(reader: R) -> Result<Vec<Entry>, ParseError> {
+    parse_with_options(reader, &ParseOptions::default())
+}
+
+/// Like `parse`, but with different keywords, e.g. to parse files written in another language.
+pub fn parse_with_keywords<R: Read>(reader: R, keywords: &Keywords) -> Result<Vec<Entry>, ParseError> {
+    parse_with_locale(reader, keywords, Locale::default())
+}
+
+/// Like `parse_with_keywords`, but with the month names written in the language of `locale`.
+pub fn parse_with_locale<R: Read>(reader: R, keywords: &Keywords, locale: Locale) -> Result<Vec<Entry>, ParseError> {
+    parse_with_options(reader, &ParseOptions::default().keywords(keywords.clone()).locale(locale))
+}
+
+/// Like `parse`, but with `options`.
+pub fn parse_with_options<R: Read>(reader: R, options: &ParseOptions) -> Result<Vec<Entry>, ParseError> {
+    collect_entries(reader, options, 0)
+}
+
+/// Like `parse`, but reserving space for `hint` entries up front to avoid reallocations when the number of entries
+/// is known approximately.
+pub fn parse_with_capacity<R: Read>(reader: R, hint: usize) -> Result<Vec<Entry>, ParseError> {
+    collect_entries(reader, default_options(), hint)
+}
+
+fn collect_entries<R: Read>(reader: R, options: &ParseOptions, capacity: usize) -> Result<Vec<Entry>, ParseError> {
+    let mut entries = Vec::with_capacity(capacity);
+    for result in EntryReader::with_options(reader, options.clone()) {
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(error) => {
+                if let ErrorKind::Io(_) = error.kind {
+                    return Err(error);
+                }
+            },
+        }
+    }
+    Ok(entries)
+}
+
+/// Like `parse`, but with the byte range of each entry in the input, from the start of its first line to the end of its
+/// last line, without the line terminator.
+pub fn parse_with_spans<R: Read>(reader: R) -> Result<Vec<(Entry, Range<usize>)>, ParseError> {
+    let mut reader = BufReader::new(reader);
+    let mut entries = vec![];
+    let mut joiner = LineJoiner::default();
+    let mut buffer = String::new();
+    let mut line_number = 0;
+    let mut offset = 0;
+    loop {
+        buffer.clear();
+        let size = reader.read_line(&mut buffer)
+            .map_err(|error| ParseError::new(ErrorKind::Io(error), line_number + 1))?;
+        let joined =
+            if size == 0 {
+                joiner.finish()
+            }
+            else {
+                line_number += 1;
+                let line = buffer.trim_end_matches(&['\n', '\r'][..]);
+                let span = offset..offset + line.len();
+                offset += size;
+                joiner.push(line.to_string(), line_number, span, &default_options().lowercase_keywords)
+            };
+        if let Some(joined) = joined {
+            if let Some(Ok(entry)) = parse_line(&joined.text, joined.first_line, default_options()) {
+                entries.push((entry, joined.span));
+            }
+        }
+        if size == 0 {
+            return Ok(entries);
+        }
+    }
+}
+
+/// Like `parse`, but wrap the entries in a `Schedule`.
+pub fn parse_schedule<R: Read>(reader: R) -> Result<Schedule, ParseError> {
+    parse(reader).map(Schedule::new)
+}
+
+/// Parse each reader like `parse` and merge their entries, sorted chronologically.
+/// The entries starting at the same time keep the order of their readers.
+pub fn parse_many<R: Read>(readers: Vec<R>) -> Result<Vec<Entry>, ParseError> {
+    let mut entries = vec![];
+    for (index, reader) in readers.into_iter().enumerate() {
+        let reader_entries = parse(reader).map_err(|mut error| {
+            error.reader = Some(index);
+            error
+        })?;
+        entries.extend(reader_entries);
+    }
+    // The sort is stable and only compares the start so that the order of the readers is kept.
+    entries.sort_by_key(|entry| (entry.date, entry.time));
+    Ok(entries)
+}
+
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>, FileError> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|error| FileError::Open {
+            path: path.to_path_buf(),
+            error,
+        })?;
+    // A missing size only loses the reservation.
+    let size = file.metadata().map(|metadata| metadata.len() as usize).unwrap_or(0);
+    parse_with_capacity(file, (size / BYTES_PER_ENTRY).min(MAX_RESERVED_ENTRIES))
+        .map_err(|error| FileError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// Parse every valid entry, collecting the lines that could not be parsed along with their line
+/// number. Blank lines are ignored and an I/O error stops the parsing.
+pub fn parse_with_warnings<R: Read>(reader: R) -> (Vec<Entry>, Vec<(usize, ParseError)>) {
+    let mut entries = vec![];
+    let mut warnings = vec![];
+    for result in EntryReader::new(reader) {
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(error) => warnings.push((error.line, error)),
+        }
+    }
+    (entries, warnings)
+}
+
+/// Like `parse`, but fails on the first line that is not a valid entry instead of skipping it.
+/// Blank lines are still ignored.
+pub fn parse_strict<R: Read>(reader: R) -> Result<Vec<Entry>, ParseError> {
+    EntryReader::new(reader).collect()
+}
+
+/// Like `parse`, but reading from an asynchronous source.
+#[cfg(feature = "tokio")]
+pub fn parse_async<R: AsyncRead + Unpin>(reader: R) -> impl Future<Output = Result<Vec<Entry>, ParseError>> {
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+    let mut entries = vec![];
+    let mut line_number = 0;
+    let mut joiner = LineJoiner::default();
+    future::poll_fn(move |context| {
+        loop {
+            let line =
+                match Pin::new(&mut lines).poll_next_line(context) {
+                    Poll::Ready(line) => line,
+                    Poll::Pending => return Poll::Pending,
+                };
+            line_number += 1;
+            match line {
+                Ok(Some(line)) => {
+                    let keywords = &default_options().lowercase_keywords;
+                    if let Some(joined) = joiner.push(line, line_number, 0..0, keywords) {
+                        if let Some(Ok(entry)) = parse_line(&joined.text, joined.first_line, default_options()) {
+                            entries.push(entry);
+                        }
+                    }
+                },
+                Ok(None) => {
+                    if let Some(joined) = joiner.finish() {
+                        if let Some(Ok(entry)) = parse_line(&joined.text, joined.first_line, default_options()) {
+                            entries.push(entry);
+                        }
+                    }
+                    return Poll::Ready(Ok(mem::take(&mut entries)));
+                },
+                Err(error) => return Poll::Ready(Err(ParseError::new(ErrorKind::Io(error), line_number))),
+            }
+        }
+    })
+}
+
+/// Like `parse`, but for data that is already in memory.
+pub fn parse_str(input: &str) -> Result<Vec<Entry>, ParseError> {
+    // Reading from memory cannot fail.
+    let entries = EntryReader::new(input.as_bytes())
+        .filter_map(Result::ok)
+        .collect();
+    Ok(entries)
+}
+
+/// Get the length of `line` without the backslash ending it when its message continues on the next line.
+/// Only a message can be continued, and the whitespace before the backslash is dropped, while blank lines and
+/// comments are never continued.
+fn continuation(line: &str, keywords: &Keywords) -> Option<usize> {
+    let line = line.trim_end_matches('\r');
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let mut words = line.split_whitespace();
+    if line.ends_with('\\') && words.any(|word| eq_lowercase(word, &keywords.msg)) {
+        Some(line[..line.len() - 1].trim_end().len())
+    }
+    else {
+        None
+    }
+}
+
+/// Parse a single line, returning `None` if there is nothing to parse on it, i.e. it is blank or a comment.
+/// The byte order mark that may start the first line is skipped.
+fn parse_line(line: &str, line_number: usize, options: &ParseOptions) -> Option<Result<Entry, ParseError>> {
+    let line =
+        if line_number == 1 {
+            line.strip_prefix(BOM).unwrap_or(line)
+        }
+        else {
+            line
+        };
+    // Lines of files written on Windows may keep their carriage return.
+    let line = line.trim_end_matches('\r');
+    if let Some(max_line_bytes) = options.max_line_bytes {
+        if line.len() > max_line_bytes {
+            return Some(Err(ParseError::new(ErrorKind::LineTooLong(line.len()), line_number)));
+        }
+    }
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let mut parser = Parser::with_options(line, line_number, options);
+    Some(parser.entry())
+}
+
+/// Format a duration as `H:MM`, or `H:MM:SS` when it does not fall on a minute, the hours going past 24 for the
+/// durations longer than a day.
+pub fn format_duration(duration: &Duration) -> String {
+    let seconds = duration.as_secs();
+    let mut output = format!("{}:{:02}", seconds / 60 / 60, seconds / 60 % 60);
+    if !seconds.is_multiple_of(60) {
+        output.push_str(&format!(":{:02}", seconds % 60));
+    }
+    output
+}
+
+/// Format the date, or the recurrence, the time and the duration of the entry as the cells of a table, a missing
+/// value giving an empty cell.
+fn format_cells(entry: &Entry) -> [String; 3] {
+    let date =
+        match (entry.recurrence, entry.date) {
+            (Some(recurrence), _) => recurrence.to_string(),
+            (None, Some(date)) => date.to_string(),
+            (None, None) => String::new(),
+        };
+    let time = entry.time.map(|time| time.to_string()).unwrap_or_default();
+    let duration = entry.duration.as_ref().map(format_duration).unwrap_or_default();
+    [date, time, duration]
+}
+
+/// Parse a duration written with units, like `1h30m`, `90m` or `2h`.
+/// A number without unit is a number of minutes.
+fn parse_duration(word: &str) -> Result<Duration, ErrorKind> {
+    let mut minutes: u64 = 0;
+    let mut rest = word;
+    while !rest.is_empty() {
+        let digits = rest.find(|char: char| !char.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(ErrorKind::InvalidDuration(word.to_string()));
+        }
+        let number: u64 = rest[..digits].parse().map_err(ErrorKind::InvalidNumber)?;
+        rest = &rest[digits..];
+        let mut chars = rest.chars();
+        let multiplier =
+            match chars.next() {
+                Some('h') | Some('H') => 60,
+                Some('m') | Some('M') | None => 1,
+                Some(_) => return Err(ErrorKind::InvalidDuration(word.to_string())),
+            };
+        rest = chars.as_str();
+        minutes = number.checked_mul(multiplier)
+            .and_then(|minutes_part| minutes.checked_add(minutes_part))
+            .ok_or_else(|| ErrorKind::InvalidDuration(word.to_string()))?;
+    }
+    let seconds = minutes.checked_mul(60).ok_or_else(|| ErrorKind::InvalidDuration(word.to_string()))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parse the French abbreviation or full name of a month, ignoring case.
+fn parse_french_month(word: &str) -> Result<Month, ErrorKind> {
+    parse_month_prefix(word, &FRENCH_MONTH_NAMES)
+}
+
+/// Parse a month written as the start, of at least three letters, of one of the month names `names`, ignoring case.
+/// A start shared by several names is ambiguous, while a start shorter than three letters is invalid.
+fn parse_month_prefix(word: &str, names: &[&str; 12]) -> Result<Month, ErrorKind> {
+    let prefix = word.to_lowercase();
+    if prefix.chars().count() < 3 {
+        return Err(ErrorKind::InvalidMonth(prefix));
+    }
+    let mut months = MONTHS.iter().zip(names)
+        .filter(|&(_, name)| name.starts_with(&prefix))
+        .map(|(&month, _)| month);
+    match (months.next(), months.next()) {
+        (Some(_), Some(_)) => Err(ErrorKind::AmbiguousMonth(prefix)),
+        (Some(month), None) => Ok(month),
+        _ => Err(ErrorKind::InvalidMonth(prefix)),
+    }
+}
+
+/// Format a date as `YYYY-MM-DD`, as read by `parse_iso_date`.
+fn format_iso_date(date: &Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year, date.month.as_index() + 1, date.day)
+}
+
+/// Parse a date written as `YYYY-MM-DD`, without checking that the day exists in the month.
+fn parse_iso_date(word: &str) -> Result<Date, ErrorKind> {
+    let mut parts = word.splitn(3, '-');
+    let year = parts.next().unwrap_or_default().parse().map_err(ErrorKind::InvalidNumber)?;
+    let month = parts.next().unwrap_or_default();
+    if month.is_empty() || !month.chars().all(|char| char.is_ascii_digit()) {
+        return Err(ErrorKind::InvalidMonth(month.to_string()));
+    }
+    let month = parse_month(month, Locale::default())?;
+    let day = parts.next().unwrap_or_default().parse().map_err(ErrorKind::InvalidNumber)?;
+    Ok(Date {
+        day,
+        month,
+        year,
+    })
+}
+
+/// Parse a month written either as a name in the language of `locale` or as a number between 1 and 12.
+fn parse_month(word: &str, locale: Locale) -> Result<Month, ErrorKind> {
+    if !word.is_empty() && word.chars().all(|char| char.is_ascii_digit()) {
+        return match word.parse::<u8>() {
+            Ok(number) if number >= 1 => Month::try_from(number - 1)
+                .map_err(|_| ErrorKind::InvalidMonth(word.to_string())),
+            _ => Err(ErrorKind::InvalidMonth(word.to_string())),
+        };
+    }
+    match locale {
+        Locale::English => word.parse(),
+        Locale::French => parse_french_month(word),
+    }
+}
+
+/// Parse a message between double quotes, in which `\"` is a literal quote and `\\` a literal backslash. Nothing but
+/// whitespace can follow the closing quote, or else an inline comment when `comments` is set, otherwise `None` is
+/// returned and the message is taken verbatim.
+fn parse_quoted(text: &str, comments: bool) -> Option<String> {
+    let mut message = String::new();
+    let mut chars = text.strip_prefix('"')?.chars();
+    while let Some(char) = chars.next() {
+        let rest = chars.as_str().trim();
+        match char {
+            '"' if rest.is_empty() || (comments && rest.starts_with('#')) => return Some(message),
+            '"' => break,
+            '\\' if chars.as_str().starts_with(['"', '\\']) => {
+                message.push(chars.next().expect("escaped char"));
+            },
+            _ => message.push(char),
+        }
+    }
+    None
+}
+
+/// Remove the comment ending `text`, which starts with a `#` at the start of a word.
+fn strip_comment(text: &str) -> &str {
+    let mut previous = ' ';
+    for (index, char) in text.char_indices() {
+        if char == '#' && previous.is_whitespace() {
+            return &text[..index];
+        }
+        previous = char;
+    }
+    text
+}
+
+/// Parse a time of the day, either in the 24-hour format (`19:00`), in the 12-hour format (`7:00pm`) or as `noon` or
+/// `midnight`.
+fn parse_time_of_day(word: &str) -> Result<Time, ErrorKind> {
+    let lowercase = word.to_lowercase();
+    match lowercase.as_str() {
+        "midnight" => return Ok(Time { hour: 0, minute: 0, second: 0 }),
+        "noon" => return Ok(Time { hour: 12, minute: 0, second: 0 }),
+        _ => (),
+    }
+    let (time, pm) =
+        if lowercase.ends_with("am") {
+            (&word[..word.len() - 2], false)
+        }
+        else if lowercase.ends_with("pm") {
+            (&word[..word.len() - 2], true)
+        }
+        else {
+            let time = parse_time(word)?;
+            if time.hour > 23 {
+                return Err(ErrorKind::InvalidHour(time.hour));
+            }
+            return Ok(time);
+        };
+    let mut time =
+        if time.contains(':') {
+            parse_time(time)?
+        }
+        else {
+            Time {
+                hour: time.parse().map_err(ErrorKind::InvalidNumber)?,
+                minute: 0,
+                second: 0,
+            }
+        };
+    if time.hour < 1 || time.hour > 12 {
+        return Err(ErrorKind::InvalidHour12(time.hour));
+    }
+    // 12am is midnight and 12pm is noon.
+    time.hour %= 12;
+    if pm {
+        time.hour += 12;
+    }
+    Ok(time)
+}
+
+fn parse_time(time: &str) -> Result<Time, ErrorKind> {
+    let (hour, minute, second) = parse_clock(time)?;
+    Ok(Time {
+        hour,
+        minute,
+        second,
+    })
+}
+
+/// Parse a duration written as `H:MM` or `H:MM:SS`, the hours going past 24 for the durations longer than a day.
+fn parse_clock_duration(duration: &str) -> Result<Duration, ErrorKind> {
+    let (hours, minutes, seconds) = parse_clock::<u64>(duration)?;
+    let seconds = hours.checked_mul(60 * 60)
+        .and_then(|hours| hours.checked_add(minutes as u64 * 60 + seconds as u64))
+        .ok_or_else(|| ErrorKind::InvalidDuration(duration.to_string()))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Parse the hours, the minutes and the seconds of `H:MM` or `H:MM:SS`, without limit on the hours.
+fn parse_clock<H: FromStr<Err=ParseIntError>>(time: &str) -> Result<(H, u8, u8), ErrorKind> {
+    let mut parts = time.split(':');
+    let hour = parts.next()
+        .ok_or_else(|| ErrorKind::InvalidTime(time.to_string()))?
+        .parse()
+        .map_err(ErrorKind::InvalidNumber)?;
+    let minute = parts.next()
+        .ok_or_else(|| ErrorKind::InvalidTime(time.to_string()))?
+        .parse()
+        .map_err(ErrorKind::InvalidNumber)?;
+    if minute > 59 {
+        return Err(ErrorKind::InvalidMinute(minute));
+    }
+    let second = match parts.next() {
+        Some(second) => second.parse().map_err(ErrorKind::InvalidNumber)?,
+        None => 0,
+    };
+    if second > 59 {
+        return Err(ErrorKind::InvalidSecond(second));
+    }
+    if parts.next().is_some() {
+        return Err(ErrorKind::InvalidTime(time.to_string()));
+    }
+    Ok((hour, minute, second))
+}
+
+/// The two-digit years from this one are in the 1900s and the two-digit years before it are in the 2000s, e.g.
+/// `99` is 1999 and `18` is 2018.
+pub const TWO_DIGIT_YEAR_PIVOT: u16 = 70;
+
+/// The keywords of the grammar, which are matched without regard to case.
+/// The default is the English set `REM`, `AT`, `DURATION`, `UNTIL`, `OMIT`, `SKIP`, `PRIORITY`, `TAG` and `MSG`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Keywords {
+    pub at: String,
+    pub duration: String,
+    pub msg: String,
+    pub omit: String,
+    pub priority: String,
+    pub rem: String,
+    pub skip: String,
+    pub tag: String,
+    pub until: String,
+}
+
+impl Keywords {
+    /// Check whether `word` is one of the keywords, which must be in lowercase.
+    fn contains(&self, word: &str) -> bool {
+        let keywords = [&self.rem, &self.at, &self.duration, &self.until, &self.omit, &self.skip, &self.priority,
+            &self.tag, &self.msg];
+        keywords.iter()
+            .any(|keyword| eq_lowercase(word, keyword))
+    }
+
+    fn to_lowercase(&self) -> Keywords {
+        Keywords {
+            at: self.at.to_lowercase(),
+            duration: self.duration.to_lowercase(),
+            msg: self.msg.to_lowercase(),
+            omit: self.omit.to_lowercase(),
+            priority: self.priority.to_lowercase(),
+            rem: self.rem.to_lowercase(),
+            skip: self.skip.to_lowercase(),
+            tag: self.tag.to_lowercase(),
+            until: self.until.to_lowercase(),
+        }
+    }
+}
+
+/// Check whether `word` is equal to `lowercase`, which is already in lowercase, ignoring the case of `word`.
+fn eq_lowercase(word: &str, lowercase: &str) -> bool {
+    word.chars().flat_map(char::to_lowercase).eq(lowercase.chars())
+}
+
+impl Default for Keywords {
+    fn default() -> Self {
+        Self {
+            at: "AT".to_string(),
+            duration: "DURATION".to_string(),
+            msg: "MSG".to_string(),
+            omit: "OMIT".to_string(),
+            priority: "PRIORITY".to_string(),
+            rem: "REM".to_string(),
+            skip: "SKIP".to_string(),
+            tag: "TAG".to_string(),
+            until: "UNTIL".to_string(),
+        }
+    }
+}
+
+/// The language in which the month names are written.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+/// The options changing how the entries are parsed, set with builder-style methods starting from the default ones.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    default_duration: Option<Duration>,
+    inline_comments: bool,
+    iso_dates: bool,
+    keywords: Keywords,
+    locale: Locale,
+    // The keywords in lowercase, to match the words without allocating.
+    lowercase_keywords: Keywords,
+    max_line_bytes: Option<usize>,
+    reject_cross_midnight: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        let keywords = Keywords::default();
+        Self {
+            default_duration: None,
+            inline_comments: false,
+            iso_dates: false,
+            lowercase_keywords: keywords.to_lowercase(),
+            keywords,
+            locale: Locale::default(),
+            max_line_bytes: None,
+            reject_cross_midnight: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Set the duration given to the entries without `DURATION` clause. By default, their duration is `None`.
+    /// With `reject_cross_midnight`, the entries whose default duration continues past midnight are rejected too.
+    pub fn default_duration(mut self, duration: Option<Duration>) -> Self {
+        self.default_duration = duration;
+        self
+    }
+
+    /// Remove the comment starting with a `#` word at the end of the messages, like in `MSG Event # to confirm`.
+    /// A `#` inside a quoted message is kept. By default, the `#` and what follows are part of the message.
+    pub fn inline_comments(mut self, enabled: bool) -> Self {
+        self.inline_comments = enabled;
+        self
+    }
+
+    /// Accept the dates written as `YYYY-MM-DD`, like `2018-03-30`, in addition to the other forms. By default, only
+    /// the dates written as a month, a day and a year are accepted.
+    pub fn iso_dates(mut self, enabled: bool) -> Self {
+        self.iso_dates = enabled;
+        self
+    }
+
+    /// Set the keywords of the grammar. The default is the English set.
+    pub fn keywords(mut self, keywords: Keywords) -> Self {
+        self.lowercase_keywords = keywords.to_lowercase();
+        self.keywords = keywords;
+        self
+    }
+
+    /// Set the language of the month names. The default is English.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Reject the lines longer than `max_line_bytes` bytes without parsing them. By default, there is no limit.
+    pub fn max_line_bytes(mut self, max_line_bytes: Option<usize>) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self
+    }
+
+    /// Reject the entries whose duration, including the default one, continues past midnight. By default, they are
+    /// accepted and end on the next day.
+    pub fn reject_cross_midnight(mut self, reject: bool) -> Self {
+        self.reject_cross_midnight = reject;
+        self
+    }
+}
+
+fn default_options() -> &'static ParseOptions {
+    static OPTIONS: OnceLock<ParseOptions> = OnceLock::new();
+    OPTIONS.get_or_init(ParseOptions::default)
+}
+
+/// A whitespace-separated token of a line, borrowing the text as written from the line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Token<'a> {
+    /// One of the keywords, in any case.
+    Keyword(&'a str),
+    /// A sequence of digits.
+    Number(&'a str),
+    /// Digits separated by colons, like `19:00`.
+    TimeLiteral(&'a str),
+    Word(&'a str),
+}
+
+impl<'a> Token<'a> {
+    fn new(text: &'a str, keywords: &Keywords) -> Self {
+        let is_digits = |part: &str| !part.is_empty() && part.chars().all(|char| char.is_ascii_digit());
+        if keywords.contains(text) {
+            Token::Keyword(text)
+        }
+        else if is_digits(text) {
+            Token::Number(text)
+        }
+        else if text.contains(':') && text.split(':').all(is_digits) {
+            Token::TimeLiteral(text)
+        }
+        else {
+            Token::Word(text)
+        }
+    }
+
+    pub fn text(&self) -> &'a str {
+        match *self {
+            Token::Keyword(text) | Token::Number(text) | Token::TimeLiteral(text) | Token::Word(text) => text,
+        }
+    }
+}
+
+/// Split a line into tokens, recognizing the default keywords.
+pub fn tokenize(line: &str) -> Vec<Token<'_>> {
+    tokenize_with_offsets(line, &default_options().lowercase_keywords).into_iter()
+        .map(|(_, token)| token)
+        .collect()
+}
+
+/// Split a line into tokens, along with the byte offset of each token in the line. The keywords must be in lowercase.
+fn tokenize_with_offsets<'a>(line: &'a str, keywords: &Keywords) -> Vec<(usize, Token<'a>)> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (index, char) in line.char_indices() {
+        if char.is_whitespace() {
+            if let Some(start) = start.take() {
+                tokens.push((start, Token::new(&line[start..index], keywords)));
+            }
+        }
+        else if start.is_none() {
+            start = Some(index);
+        }
+    }
+    if let Some(start) = start {
+        tokens.push((start, Token::new(&line[start..], keywords)));
+    }
+    tokens
+}
+
+/// A parser for one line, which is split into tokens borrowing the line when the parser is created.
+/// The parser keeps the index of the next token: each method parses one part of the grammar starting at that token
+/// and moves the index after the tokens it consumed, so that the parts can be parsed one after the other. On error,
+/// the index is left after the last token that was read.
+pub struct Parser<'a> {
+    index: usize,
+    line: usize,
+    // The byte offset of each token in the text.
+    offsets: Vec<usize>,
+    options: &'a ParseOptions,
+    text: &'a str,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    /// Create a parser for the (1-based) line `line_number`, with the default options.
+    pub fn new(line: &'a str, line_number: usize) -> Self {
+        Parser::with_options(line, line_number, default_options())
+    }
+
+    pub fn with_options(line: &'a str, line_number: usize, options: &'a ParseOptions) -> Self {
+        let (offsets, tokens) = tokenize_with_offsets(line, &options.lowercase_keywords).into_iter().unzip();
+        Self {
+            index: 0,
+            line: line_number,
+            offsets,
+            options,
+            text: line,
+            tokens,
+        }
+    }
+
+    /// Parse a number of days of advance warning, like `+3`.
+    fn advance(&mut self) -> Result<u8, ParseError> {
+        let days = self.next_word()?[1..].parse();
+        days.map_err(|error| self.error(ErrorKind::InvalidNumber(error)))
+    }
+
+    /// Parse a date, like `Mar 30 2018`.
+    pub fn date(&mut self) -> Result<Date, ParseError> {
+        let start = self.index;
+        let iso = self.options.iso_dates && self.peek_word().is_some_and(|word| word.split('-').count() == 3);
+        let date =
+            if iso {
+                let date = parse_iso_date(self.next_word()?);
+                date.map_err(|kind| self.error(kind))?
+            }
+            else {
+                let locale = self.options.locale;
+                let month = parse_month(self.next_word()?, locale);
+                let month = month.map_err(|kind| self.error(kind))?;
+                let day = self.num()?;
+                let year = self.year()?;
+                Date {
+                    day,
+                    month,
+                    year,
+                }
+            };
+        if let Err(kind) = date.validate() {
+            let mut error = self.error(kind);
+            error.span = Some(self.span(start).start..self.span(self.index - 1).end);
+            return Err(error);
+        }
+        Ok(date)
+    }
+
+    /// Parse a `DURATION` clause, the duration being either written as `H:MM` or with units, like `1h30m`, `90m` or
+    /// `2h`.
+    /// A zero duration, like `0:00`, is allowed to mark an instant.
+    pub fn duration(&mut self) -> Result<Duration, ParseError> {
+        self.ident(|keywords| &keywords.duration)?;
+        if let Some(word) = self.peek_word().filter(|word| word.starts_with('-')) {
+            let kind = ErrorKind::NegativeDuration(word.to_string());
+            return Err(self.error_at(kind, self.index));
+        }
+        if self.peek_word().is_some_and(|word| word.contains(':')) {
+            let duration = parse_clock_duration(self.next_word()?);
+            return duration.map_err(|kind| self.error(kind));
+        }
+        let duration = parse_duration(self.next_word()?);
+        duration.map_err(|kind| self.error(kind))
+    }
+
+    /// Parse a whole entry, starting with `REM`.
+    pub fn entry(&mut self) -> Result<Entry, ParseError> {
+        self.ident(|keywords| &keywords.rem)?;
+        let (date, recurrence) =
+            match self.recurrence()? {
+                Some(recurrence) => (None, Some(recurrence)),
+                None => (Some(self.date()?), None),
+            };
+        let advance_days =
+            match self.peek_word() {
+                Some(word) if word.starts_with('+') => self.advance()?,
+                _ => 0,
+            };
+        let mut duration = None;
+        let mut duration_index = None;
+        let mut omit = vec![];
+        let mut priority = None;
+        let mut skip = None;
+        let mut tags = vec![];
+        let mut time = None;
+        let mut time_index = None;
+        let mut until = None;
+        // The optional clauses can come in any order until MSG, those other than OMIT and TAG only once.
+        loop {
+            if self.peek_ident(|keywords| &keywords.at) {
+                if time.is_some() {
+                    return Err(self.duplicate_keyword());
+                }
+                time_index = Some(self.index);
+                time = Some(self.time()?);
+            }
+            else if self.peek_ident(|keywords| &keywords.duration) {
+                if duration.is_some() {
+                    return Err(self.duplicate_keyword());
+                }
+                duration_index = Some(self.index + 1);
+                duration = Some(self.duration()?);
+            }
+            else if self.peek_ident(|keywords| &keywords.until) {
+                if until.is_some() {
+                    return Err(self.duplicate_keyword());
+                }
+                until = Some(self.until()?);
+            }
+            else if self.peek_ident(|keywords| &keywords.omit) {
+                omit.push(self.omit()?);
+            }
+            else if self.peek_ident(|keywords| &keywords.priority) {
+                if priority.is_some() {
+                    return Err(self.duplicate_keyword());
+                }
+                priority = Some(self.priority()?);
+            }
+            else if self.peek_ident(|keywords| &keywords.skip) {
+                if skip.is_some() {
+                    return Err(self.duplicate_keyword());
+                }
+                skip = Some(self.skip()?);
+            }
+            else if self.peek_ident(|keywords| &keywords.tag) {
+                tags.push(self.tag()?);
+            }
+            else {
+                break;
+            }
+        }
+        // The default duration is checked like an explicit one, the error pointing to the AT clause, or to REM for an
+        // all-day entry.
+        let duration_index = duration_index.or(time_index).unwrap_or(0);
+        let duration = duration.or(self.options.default_duration);
+        if let Some(duration) = duration {
+            let start = time.map_or(0, |time| {
+                time.hour as u64 * 60 * 60 + time.minute as u64 * 60 + time.second as u64
+            });
+            if self.options.reject_cross_midnight && duration.as_secs() > SECONDS_PER_DAY - start {
+                return Err(self.error_at(ErrorKind::DurationCrossesMidnight, duration_index));
+            }
+        }
+        let msg =
+            if self.index < self.tokens.len() {
+                self.message()?
+            }
+            else {
+                String::new()
+            };
+        Ok(Entry {
+            advance_days,
+            date,
+            duration,
+            msg,
+            omit,
+            priority,
+            recurrence,
+            skip: skip.unwrap_or_default(),
+            tags,
+            time,
+            until,
+        })
+    }
+
+    /// Report the keyword at the current index as a duplicate.
+    fn duplicate_keyword(&self) -> ParseError {
+        let keyword = self.tokens[self.index].text().to_string();
+        self.error_at(ErrorKind::DuplicateKeyword(keyword), self.index)
+    }
+
+    /// Create an error pointing to the last token that was consumed.
+    fn error(&self, kind: ErrorKind) -> ParseError {
+        self.error_at(kind, self.index.saturating_sub(1))
+    }
+
+    fn error_at(&self, kind: ErrorKind, index: usize) -> ParseError {
+        let mut error = ParseError::new(kind, self.line);
+        error.span = Some(self.span(index));
+        error
+    }
+
+    fn ident(&mut self, keyword: fn(&Keywords) -> &String) -> Result<(), ParseError> {
+        if !self.peek_ident(keyword) {
+            return Err(self.error_at(ErrorKind::ExpectedKeyword {
+                expected: keyword(&self.options.keywords).clone(),
+                found: self.peek_word().map(ToString::to_string),
+            }, self.index));
+        }
+        self.index += 1;
+        Ok(())
+    }
+
+    /// Get the index of the next token to parse.
+    pub fn position(&self) -> usize {
+        self.index
+    }
+
+    /// Get the tokens that are not parsed yet.
+    pub fn remaining(&self) -> &[Token<'a>] {
+        &self.tokens[self.index..]
+    }
+
+    /// Parse a `MSG` clause, taking the rest of the line as the message.
+    /// A message between double quotes keeps its surrounding whitespace and can contain quotes written as `\"`.
+    pub fn message(&mut self) -> Result<String, ParseError> {
+        self.ident(|keywords| &keywords.msg)?;
+        // Take the rest of the line verbatim to keep the whitespace inside the message.
+        let message =
+            match self.offsets.get(self.index) {
+                Some(&offset) => {
+                    let text = &self.text[offset..];
+                    if let Some(message) = parse_quoted(text, self.options.inline_comments) {
+                        message
+                    }
+                    else if self.options.inline_comments {
+                        strip_comment(text).trim_end().to_string()
+                    }
+                    else {
+                        text.trim_end().to_string()
+                    }
+                },
+                None => String::new(),
+            };
+        self.index = self.tokens.len();
+        Ok(message)
+    }
+
+    fn next_word(&mut self) -> Result<&str, ParseError> {
+        if self.index >= self.tokens.len() {
+            return Err(self.error_at(ErrorKind::UnexpectedEndOfLine, self.index));
+        }
+        self.index += 1;
+        Ok(self.tokens[self.index - 1].text())
+    }
+
+    fn num<N: FromStr<Err=ParseIntError>>(&mut self) -> Result<N, ParseError> {
+        let num = self.next_word()?.parse();
+        num.map_err(|error| self.error(ErrorKind::InvalidNumber(error)))
+    }
+
+    /// Check whether the next token is the keyword selected by `keyword` among the keywords of the options.
+    fn peek_ident(&self, keyword: fn(&Keywords) -> &String) -> bool {
+        match self.tokens.get(self.index) {
+            Some(Token::Keyword(word)) => eq_lowercase(word, keyword(&self.options.lowercase_keywords)),
+            _ => false,
+        }
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        self.tokens.get(self.index)
+            .map(Token::text)
+    }
+
+    fn omit(&mut self) -> Result<Date, ParseError> {
+        self.ident(|keywords| &keywords.omit)?;
+        self.date()
+    }
+
+    fn priority(&mut self) -> Result<u16, ParseError> {
+        self.ident(|keywords| &keywords.priority)?;
+        let priority = self.num()?;
+        if priority > MAX_PRIORITY {
+            return Err(self.error(ErrorKind::InvalidPriority(priority)));
+        }
+        Ok(priority)
+    }
+
+    /// Parse a weekday for a weekly recurrence or `*` followed by a day for a monthly recurrence, if any.
+    fn recurrence(&mut self) -> Result<Option<Recurrence>, ParseError> {
+        if self.peek_word() == Some("*") {
+            self.index += 1;
+            let day = self.num()?;
+            if day == 0 || day > 31 {
+                return Err(self.error(ErrorKind::InvalidDay(day)));
+            }
+            return Ok(Some(Recurrence::Monthly { day }));
+        }
+        match self.peek_word().and_then(parse_weekday) {
+            Some(weekday) => {
+                self.index += 1;
+                Ok(Some(Recurrence::Weekly(weekday)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Get the byte range of the token at `index`, or the end of the line if there is no such token.
+    fn span(&self, index: usize) -> Range<usize> {
+        match (self.offsets.get(index), self.tokens.get(index)) {
+            (Some(&offset), Some(token)) => offset..offset + token.text().len(),
+            _ => self.text.len()..self.text.len(),
+        }
+    }
+
+    /// Parse a `SKIP` clause, followed by `WEEKEND` or `NONE`.
+    fn skip(&mut self) -> Result<SkipRule, ParseError> {
+        self.ident(|keywords| &keywords.skip)?;
+        let word = self.next_word()?;
+        match word.to_lowercase().as_str() {
+            "none" => Ok(SkipRule::None),
+            "weekend" => Ok(SkipRule::Weekend),
+            _ => {
+                let kind = ErrorKind::InvalidSkipRule(word.to_string());
+                Err(self.error(kind))
+            },
+        }
+    }
+
+    fn tag(&mut self) -> Result<String, ParseError> {
+        self.ident(|keywords| &keywords.tag)?;
+        Ok(self.next_word()?.to_string())
+    }
+
+    /// Parse an `AT` clause, the time being either in the 24-hour or in the 12-hour format.
+    pub fn time(&mut self) -> Result<Time, ParseError> {
+        self.ident(|keywords| &keywords.at)?;
+        let time = parse_time_of_day(self.next_word()?);
+        time.map_err(|kind| self.error(kind))
+    }
+
+    fn until(&mut self) -> Result<Date, ParseError> {
+        self.ident(|keywords| &keywords.until)?;
+        self.date()
+    }
+
+    /// Parse a year, expanding a year written with one or two digits with `TWO_DIGIT_YEAR_PIVOT`.
+    fn year(&mut self) -> Result<u16, ParseError> {
+        let short = self.peek_word().is_some_and(|word| word.len() <= 2);
+        let year = self.num()?;
+        if !short {
+            return Ok(year);
+        }
+        if year >= TWO_DIGIT_YEAR_PIVOT {
+            Ok(1900 + year)
+        }
+        else {
+            Ok(2000 + year)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::collections::BTreeSet;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::time::Duration;
+
+    use {Date, Entry, EntryBuilder, EntryReader, ErrorKind, FileError, Keywords, Locale, MONTHS, Month, ParseError,
+        ParseOptions, Parser, Recurrence, SkipRule, Time, Token, days_in_month, entries_on, expand, format_duration,
+        format_iso_date, is_leap_year, parse, parse_file, parse_iso_date, parse_many, parse_str, parse_strict,
+        parse_with_capacity, parse_with_keywords, parse_with_locale, parse_with_options, parse_with_spans,
+        parse_with_warnings, tokenize};
+    use Month::*;
+    use Weekday::*;
+
+    #[test]
+    fn parse_rem() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entries[0].duration, Some(Duration::from_secs(75 * 60)));
+        assert_eq!(entries[0].msg, "Event name".to_string());
+        assert_eq!(entries[0].time, Some(Time { hour: 19, minute: 0, second: 0 }));
+
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event";
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entries[0].duration, Some(Duration::from_secs(75 * 60)));
+        assert_eq!(entries[0].msg, "Event name".to_string());
+        assert_eq!(entries[0].time, Some(Time { hour: 19, minute: 0, second: 0 }));
+        assert_eq!(entries[1].date, Some(Date { day: 9, month: April, year: 2018 }));
+        let _month = entries[1].date.expect("date").month;
+        assert!(entries[1].date.expect("date").month == April);
+        assert_eq!(entries[1].duration, Some(Duration::from_secs(15 * 60)));
+        assert_eq!(entries[1].msg, "Super Event".to_string());
+        assert_eq!(entries[1].time, Some(Time { hour: 12, minute: 50, second: 0 }));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let mut parser = Parser::new("REM Foo 30 2018 AT 19:00 DURATION 1:15 MSG Event name", 1);
+        match parser.entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidMonth(month)) => assert_eq!(month, "foo"),
+            result => panic!("Expecting invalid month, found {:?}", result),
+        }
+
+        let mut parser = Parser::new("REM Mar thirty 2018 AT 19:00 DURATION 1:15 MSG Event name", 1);
+        match parser.entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidNumber(_)) => (),
+            result => panic!("Expecting invalid number, found {:?}", result),
+        }
+
+        let mut parser = Parser::new("REM Mar 30 2018 AT", 1);
+        match parser.entry().map_err(|error| error.kind) {
+            Err(ErrorKind::UnexpectedEndOfLine) => (),
+            result => panic!("Expecting end of line, found {:?}", result),
+        }
+
+        let mut parser = Parser::new("RAPPEL Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name", 1);
+        match parser.entry().map_err(|error| error.kind) {
+            Err(ErrorKind::ExpectedKeyword { expected, found }) => {
+                assert_eq!(expected, "REM");
+                assert_eq!(found, Some("RAPPEL".to_string()));
+            },
+            result => panic!("Expecting keyword, found {:?}", result),
+        }
+
+        let mut parser = Parser::new("REM Mar 30 2018 AT 19:00 DURATION 1:15 Event name", 1);
+        match parser.entry().map_err(|error| error.kind) {
+            Err(ErrorKind::ExpectedKeyword { expected, found }) => {
+                assert_eq!(expected, "MSG");
+                assert_eq!(found, Some("Event".to_string()));
+            },
+            result => panic!("Expecting keyword, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_error_line() {
+        let mut parser = Parser::new("REM Foo 30 2018 AT 19:00 DURATION 1:15 MSG Event name", 42);
+        let error = parser.entry().expect_err("error");
+        assert_eq!(error.line, 42);
+        assert_eq!(error.to_string(), "line 42: Invalid month foo");
+
+        let data: &[u8] = b"REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name\nREM Apr 9 2018 AT 12:50 MSG \xFF\n";
+        let error = parse(data).expect_err("error");
+        assert_eq!(error.line, 2);
+        match error.kind {
+            ErrorKind::Io(_) => (),
+            kind => panic!("Expecting I/O error, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn parse_strict_mode() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event
+        ";
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].msg, "Super Event".to_string());
+
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 Super Event";
+        assert_eq!(parse(data.as_bytes()).expect("entries").len(), 1);
+        let error = parse_strict(data.as_bytes()).expect_err("error");
+        assert_eq!(error.line, 2);
+        match error.kind {
+            ErrorKind::ExpectedKeyword { expected, .. } => assert_eq!(expected, "MSG"),
+            kind => panic!("Expecting keyword, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn parse_warnings() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM Feb 5 2018 AT 12:50 No message keyword
+
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event
+        REM Foo 9 2018 AT 12:50 DURATION 0:15 MSG Bad month";
+        let (entries, warnings) = parse_with_warnings(data.as_bytes());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].msg, "Event name".to_string());
+        assert_eq!(entries[1].msg, "Super Event".to_string());
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].0, 2);
+        assert_eq!(warnings[1].0, 5);
+        match warnings[1].1.kind {
+            ErrorKind::InvalidMonth(ref month) => assert_eq!(month, "foo"),
+            ref kind => panic!("Expecting invalid month, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn display_entry() {
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.to_string(), line);
+
+        let entry = Entry {
+            advance_days: 0,
+            date: Some(Date { day: 9, month: April, year: 2018 }),
+            duration: Some(Duration::from_secs(15 * 60)),
+            msg: "Super Event".to_string(),
+            omit: vec![],
+            priority: None,
+            recurrence: None,
+            skip: SkipRule::None,
+            tags: vec![],
+            time: Some(Time { hour: 7, minute: 5, second: 0 }),
+            until: None,
+        };
+        let line = entry.to_string();
+        assert_eq!(line, "REM Apr 9 2018 AT 07:05 DURATION 0:15 MSG Super Event");
+        assert_eq!(Parser::new(&line, 1).entry().expect("entry"), entry);
+    }
+
+    #[test]
+    fn display_date_time() {
+        assert_eq!(format!("{}", March), "Mar");
+        assert_eq!(format!("{}", September), "Sep");
+        assert_eq!(Date { day: 30, month: March, year: 2018 }.to_string(), "Mar 30 2018");
+        assert_eq!(Time { hour: 19, minute: 0, second: 0 }.to_string(), "19:00");
+        assert_eq!(Time { hour: 7, minute: 5, second: 0 }.to_string(), "07:05");
+    }
+
+    #[test]
+    fn parse_month() {
+        assert_eq!("mar".parse::<Month>().expect("month"), March);
+        assert_eq!("Mar".parse::<Month>().expect("month"), March);
+        assert_eq!("march".parse::<Month>().expect("month"), March);
+        assert_eq!("MARCH".parse::<Month>().expect("month"), March);
+        assert_eq!("September".parse::<Month>().expect("month"), September);
+        match "marz".parse::<Month>() {
+            Err(ErrorKind::InvalidMonth(month)) => assert_eq!(month, "marz"),
+            result => panic!("Expecting invalid month, found {:?}", result),
+        }
+
+        let data = "REM September 3 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries[0].date, Some(Date { day: 3, month: September, year: 2018 }));
+    }
+
+    #[test]
+    fn parse_numeric_month() {
+        let data = "REM 3 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM 12 25 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entries[0], entries[1]);
+        assert_eq!(entries[2].date, Some(Date { day: 25, month: December, year: 2018 }));
+
+        for month in &["0", "13"] {
+            let line = format!("REM {} 30 2018 AT 19:00 DURATION 1:15 MSG Event name", month);
+            match Parser::new(&line, 1).entry().map_err(|error| error.kind) {
+                Err(ErrorKind::InvalidMonth(ref invalid)) => assert_eq!(invalid, month),
+                result => panic!("Expecting invalid month, found {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_date() {
+        assert_eq!(days_in_month(&January, 2018), 31);
+        assert_eq!(days_in_month(&April, 2018), 30);
+        assert_eq!(days_in_month(&February, 2018), 28);
+        assert_eq!(days_in_month(&February, 2020), 29);
+        assert_eq!(days_in_month(&February, 1900), 28);
+        assert_eq!(days_in_month(&February, 2000), 29);
+
+        assert!(Date { day: 29, month: February, year: 2020 }.validate().is_ok());
+        assert!(Date { day: 31, month: December, year: 2018 }.validate().is_ok());
+        assert!(Date { day: 29, month: February, year: 2019 }.validate().is_err());
+        assert!(Date { day: 31, month: April, year: 2018 }.validate().is_err());
+        assert!(Date { day: 0, month: April, year: 2018 }.validate().is_err());
+
+        let line = "REM Feb 29 2020 AT 19:00 DURATION 1:15 MSG Event name";
+        assert!(Parser::new(line, 1).entry().is_ok());
+        let line = "REM Feb 29 2019 AT 19:00 DURATION 1:15 MSG Event name";
+        match Parser::new(line, 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidDate(date)) => assert_eq!(date, Date { day: 29, month: February, year: 2019 }),
+            result => panic!("Expecting invalid date, found {:?}", result),
+        }
+        let line = "REM Mar 286 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        assert!(Parser::new(line, 1).entry().is_err());
+    }
+
+    #[test]
+    fn validate_time() {
+        let line = "REM Mar 30 2018 AT 23:59 DURATION 1:15 MSG Event name";
+        assert!(Parser::new(line, 1).entry().is_ok());
+        let line = "REM Mar 30 2018 AT 25:00 DURATION 1:15 MSG Event name";
+        match Parser::new(line, 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidHour(25)) => (),
+            result => panic!("Expecting invalid hour, found {:?}", result),
+        }
+        let line = "REM Mar 30 2018 AT 19:99 DURATION 1:15 MSG Event name";
+        match Parser::new(line, 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidMinute(99)) => (),
+            result => panic!("Expecting invalid minute, found {:?}", result),
+        }
+        let line = "REM Mar 30 2018 AT 24:00 DURATION 1:15 MSG Event name";
+        let error = Parser::new(line, 1).entry().expect_err("error");
+        assert_eq!(error.to_string(), "line 1: Invalid hour 24, use 00:00 for midnight");
+
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 30:00 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.duration, Some(Duration::from_secs(30 * 60 * 60)));
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:60 MSG Event name";
+        match Parser::new(line, 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidMinute(60)) => (),
+            result => panic!("Expecting invalid minute, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn month_index() {
+        let months = [January, February, March, April, May, June, July, August, September, October, November,
+            December];
+        for (index, month) in months.iter().enumerate() {
+            assert_eq!(Month::try_from(index as u8).expect("month"), *month);
+            assert_eq!(month.as_index(), index as u8);
+        }
+        match Month::try_from(12) {
+            Err(ErrorKind::InvalidMonth(month)) => assert_eq!(month, "12"),
+            result => panic!("Expecting invalid month, found {:?}", result),
+        }
+        assert!(Month::try_from(255).is_err());
+    }
+
+    #[test]
+    fn sort_dates() {
+        let mut dates = vec![
+            Date { day: 1, month: March, year: 2019 },
+            Date { day: 2, month: March, year: 2018 },
+            Date { day: 31, month: December, year: 2017 },
+            Date { day: 1, month: April, year: 2018 },
+            Date { day: 1, month: March, year: 2018 },
+        ];
+        dates.sort();
+        assert_eq!(dates, vec![
+            Date { day: 31, month: December, year: 2017 },
+            Date { day: 1, month: March, year: 2018 },
+            Date { day: 2, month: March, year: 2018 },
+            Date { day: 1, month: April, year: 2018 },
+            Date { day: 1, month: March, year: 2019 },
+        ]);
+
+        let mut times = vec![
+            Time { hour: 19, minute: 0, second: 0 },
+            Time { hour: 7, minute: 30, second: 0 },
+            Time { hour: 7, minute: 15, second: 0 },
+        ];
+        times.sort();
+        assert_eq!(times, vec![
+            Time { hour: 7, minute: 15, second: 0 },
+            Time { hour: 7, minute: 30, second: 0 },
+            Time { hour: 19, minute: 0, second: 0 },
+        ]);
+    }
+
+    #[test]
+    fn sort_entries() {
+        let data = "REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Third
+        REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Second
+        REM Apr 9 2018 AT 08:00 DURATION 2:00 MSG Fourth
+        REM Mar 30 2018 AT 09:30 DURATION 0:30 MSG First
+        REM Apr 9 2018 AT 23:00 DURATION 1:00 MSG Fifth";
+        let mut entries = parse_strict(data.as_bytes()).expect("entries");
+        entries.sort();
+        let messages: Vec<_> = entries.iter().map(|entry| entry.msg.as_str()).collect();
+        assert_eq!(messages, vec!["First", "Second", "Fourth", "Third", "Fifth"]);
+
+        // Entries starting at the same time are only equal when all their fields are.
+        let mut set = BTreeSet::new();
+        set.insert(Parser::new("REM Mar 30 2018 AT 19:00 MSG Dinner", 1).entry().expect("entry"));
+        set.insert(Parser::new("REM Mar 30 2018 AT 19:00 MSG Movie", 1).entry().expect("entry"));
+        set.insert(Parser::new("REM Mar 30 2018 AT 19:00 MSG Dinner", 1).entry().expect("entry"));
+        set.insert(Parser::new("REM Mar 30 2018 AT 19:00 DURATION 1:00 MSG Dinner", 1).entry().expect("entry"));
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn entry_end() {
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let end = (Date { day: 30, month: March, year: 2018 }, Time { hour: 20, minute: 15, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+
+        let line = "REM Mar 30 2018 AT 23:30 DURATION 1:00 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let end = (Date { day: 31, month: March, year: 2018 }, Time { hour: 0, minute: 30, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+
+        let line = "REM Feb 28 2018 AT 23:30 DURATION 1:00 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let end = (Date { day: 1, month: March, year: 2018 }, Time { hour: 0, minute: 30, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+
+        let line = "REM Dec 31 2018 AT 22:00 DURATION 50:00 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let end = (Date { day: 3, month: January, year: 2019 }, Time { hour: 0, minute: 0, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+    }
+
+    #[test]
+    fn weekday() {
+        assert_eq!(Date { day: 30, month: March, year: 2018 }.weekday(), Friday);
+        assert_eq!(Date { day: 1, month: January, year: 2000 }.weekday(), Saturday);
+        assert_eq!(Date { day: 29, month: February, year: 2020 }.weekday(), Saturday);
+        assert_eq!(Date { day: 1, month: March, year: 2020 }.weekday(), Sunday);
+        assert_eq!(Date { day: 25, month: December, year: 2017 }.weekday(), Monday);
+        assert_eq!(Date { day: 14, month: October, year: 2026 }.weekday(), Wednesday);
+        assert_eq!(Date::MIN.weekday(), Saturday);
+        assert_eq!(Date { day: 29, month: February, year: 0 }.weekday(), Tuesday);
+        assert_eq!(Date::MAX.weekday(), Tuesday);
+        assert_eq!(Friday.to_string(), "Fri");
+    }
+
+    #[test]
+    fn parse_string() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        Not an entry
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event".to_string();
+        let entries = parse_str(&data).expect("entries");
+        assert_eq!(entries, parse(data.as_bytes()).expect("entries"));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].msg, "Super Event".to_string());
+    }
+
+    #[test]
+    fn parse_from_file() {
+        let path = env::temp_dir().join("rem-parse-file.rem");
+        fs::write(&path, "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name\n").expect("write");
+        let entries = parse_file(&path).expect("entries");
+        assert_eq!(entries[0].msg, "Event name".to_string());
+
+        let path = env::temp_dir().join("rem-parse-file-invalid.rem");
+        let mut file = File::create(&path).expect("file");
+        file.write_all(b"REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name\nREM \xFF\n").expect("write");
+        match parse_file(&path) {
+            Err(FileError::Parse { path: ref error_path, ref error }) => {
+                assert_eq!(error_path, &path);
+                assert_eq!(error.line, 2);
+            },
+            result => panic!("Expecting parse error, found {:?}", result),
+        }
+
+        let path = env::temp_dir().join("rem-parse-file-does-not-exist.rem");
+        match parse_file(&path) {
+            Err(FileError::Open { path: ref error_path, .. }) => assert_eq!(error_path, &path),
+            result => panic!("Expecting open error, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_comments() {
+        let data = "# Events of March
+        REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+            # REM Mar 31 2018 AT 19:00 DURATION 1:15 MSG Commented out
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event # not a comment";
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].msg, "Event name".to_string());
+        assert_eq!(entries[1].msg, "Super Event # not a comment".to_string());
+        let (entries, warnings) = parse_with_warnings(data.as_bytes());
+        assert_eq!(entries.len(), 2);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_message_whitespace() {
+        let line = "REM Mar 30 2018  AT 19:00   DURATION 1:15 MSG   Meeting    with  team  ";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.msg, "Meeting    with  team".to_string());
+
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.msg, "".to_string());
+
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Café  crème";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.msg, "Café  crème".to_string());
+    }
+
+    #[test]
+    fn parse_all_day() {
+        let line = "REM Mar 30 2018 MSG Dentist appointment";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entry.time, None);
+        assert_eq!(entry.msg, "Dentist appointment".to_string());
+        assert_eq!(entry.to_string(), line);
+
+        let line = "REM Mar 30 2018 DURATION 25:00 MSG Conference";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let end = (Date { day: 31, month: March, year: 2018 }, Time { hour: 1, minute: 0, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+
+        let data = "REM Mar 30 2018 AT 09:00 DURATION 1:00 MSG Timed
+        REM Mar 30 2018 MSG All day";
+        let mut entries = parse_strict(data.as_bytes()).expect("entries");
+        entries.sort();
+        assert_eq!(entries[0].msg, "All day".to_string());
+    }
+
+    #[test]
+    fn parse_optional_duration() {
+        let line = "REM Mar 30 2018 AT 09:00 MSG Call mom";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.time, Some(Time { hour: 9, minute: 0, second: 0 }));
+        assert_eq!(entry.duration, None);
+        assert_eq!(entry.msg, "Call mom".to_string());
+        assert_eq!(entry.to_string(), line);
+        let end = (Date { day: 30, month: March, year: 2018 }, Time { hour: 9, minute: 0, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+
+        let line = "REM Mar 30 2018 AT 09:00 DURATION 0:30 MSG Call mom";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.duration, Some(Duration::from_secs(30 * 60)));
+        assert_eq!(entry.to_string(), line);
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        let durations = [
+            ("1:30", 90),
+            ("1h30m", 90),
+            ("90m", 90),
+            ("2h", 120),
+            ("2H", 120),
+            ("45", 45),
+            ("0m", 0),
+        ];
+        for &(duration, minutes) in &durations {
+            let line = format!("REM Mar 30 2018 AT 19:00 DURATION {} MSG Event name", duration);
+            let entry = Parser::new(&line, 1).entry().expect("entry");
+            assert_eq!(entry.duration, Some(Duration::from_secs(minutes * 60)));
+        }
+
+        for duration in &["h", "1x", "1h30s", "m30", "307445734561825861h", "307445734561825861m"] {
+            let line = format!("REM Mar 30 2018 AT 19:00 DURATION {} MSG Event name", duration);
+            match Parser::new(&line, 1).entry().map_err(|error| error.kind) {
+                Err(ErrorKind::InvalidDuration(ref invalid)) => assert_eq!(invalid, duration),
+                result => panic!("Expecting invalid duration, found {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_12_hour_time() {
+        let times = [
+            ("7:00pm", Time { hour: 19, minute: 0, second: 0 }),
+            ("7:30PM", Time { hour: 19, minute: 30, second: 0 }),
+            ("7:30am", Time { hour: 7, minute: 30, second: 0 }),
+            ("7pm", Time { hour: 19, minute: 0, second: 0 }),
+            ("12:00am", Time { hour: 0, minute: 0, second: 0 }),
+            ("12:15am", Time { hour: 0, minute: 15, second: 0 }),
+            ("12:00pm", Time { hour: 12, minute: 0, second: 0 }),
+            ("19:00", Time { hour: 19, minute: 0, second: 0 }),
+        ];
+        for &(time, expected) in &times {
+            let line = format!("REM Mar 30 2018 AT {} DURATION 1:15 MSG Event name", time);
+            let entry = Parser::new(&line, 1).entry().expect("entry");
+            assert_eq!(entry.time, Some(expected));
+        }
+
+        for &(time, hour) in &[("13:00am", 13), ("0pm", 0), ("0:30am", 0)] {
+            let line = format!("REM Mar 30 2018 AT {} DURATION 1:15 MSG Event name", time);
+            match Parser::new(&line, 1).entry().map_err(|error| error.kind) {
+                Err(kind @ ErrorKind::InvalidHour12(_)) =>
+                    assert_eq!(kind.to_string(), format!("Invalid hour {}, expecting 1 to 12", hour)),
+                result => panic!("Expecting invalid hour, found {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_seconds() {
+        let line = "REM Mar 30 2018 AT 19:00:30 DURATION 0:00:45 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.time, Some(Time { hour: 19, minute: 0, second: 30 }));
+        assert_eq!(entry.duration, Some(Duration::from_secs(45)));
+        let end = (Date { day: 30, month: March, year: 2018 }, Time { hour: 19, minute: 1, second: 15 });
+        assert_eq!(entry.end(), Some(end));
+        assert_eq!(entry.to_string(), line);
+
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.time, Some(Time { hour: 19, minute: 0, second: 0 }));
+        assert_eq!(entry.to_string(), line);
+
+        let line = "REM Mar 30 2018 AT 19:00:60 MSG Event name";
+        match Parser::new(line, 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidSecond(60)) => (),
+            result => panic!("Expecting invalid second, found {:?}", result),
+        }
+        let line = "REM Mar 30 2018 AT 19:00:00:00 MSG Event name";
+        match Parser::new(line, 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidTime(_)) => (),
+            result => panic!("Expecting invalid time, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn new_date() {
+        assert_eq!(Date::new(30, March, 2018).expect("date"), Date { day: 30, month: March, year: 2018 });
+        assert_eq!(Date::new(29, February, 2020).expect("date"), Date { day: 29, month: February, year: 2020 });
+        match Date::new(30, February, 2018) {
+            Err(ErrorKind::InvalidDate(date)) => assert_eq!(date, Date { day: 30, month: February, year: 2018 }),
+            result => panic!("Expecting invalid date, found {:?}", result),
+        }
+        assert!(Date::new(0, March, 2018).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let json = ::serde_json::to_string(&entry).expect("json");
+        let deserialized: Entry = ::serde_json::from_str(&json).expect("entry");
+        assert_eq!(deserialized, entry);
+    }
+
+    #[test]
+    fn read_entries() {
+        let data: &[u8] = b"REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+
+        # Comment
+        REM Foo 9 2018 MSG Bad month
+        REM Apr 9 2018 AT 12:50 DURATION 0:15 MSG Super Event
+        REM \xFF
+        REM Apr 10 2018 MSG After the error";
+        let mut reader = EntryReader::new(data);
+        assert_eq!(reader.next().expect("entry").expect("entry").msg, "Event name".to_string());
+        assert_eq!(reader.next().expect("error").expect_err("error").line, 4);
+        assert_eq!(reader.next().expect("entry").expect("entry").msg, "Super Event".to_string());
+        let error = reader.next().expect("error").expect_err("error");
+        assert_eq!(error.line, 6);
+        match error.kind {
+            ErrorKind::Io(_) => (),
+            kind => panic!("Expecting I/O error, found {:?}", kind),
+        }
+        assert!(reader.next().is_none());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversion() {
+        use chrono::{NaiveDate, NaiveTime};
+
+        let line = "REM Mar 30 2018 AT 19:00:30 DURATION 1:15 MSG Event name";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let date = NaiveDate::from_ymd_opt(2018, 3, 30).expect("date");
+        let time = NaiveTime::from_hms_opt(19, 0, 30).expect("time");
+        assert_eq!(entry.date.expect("date").to_naive_date(), Some(date));
+        assert_eq!(entry.time.expect("time").to_naive_time(), Some(time));
+        let datetime = entry.to_naive_datetime().expect("datetime");
+        assert_eq!(datetime, date.and_time(time));
+
+        let from_chrono = Entry::from_chrono(&datetime, "Event name").expect("entry");
+        assert_eq!(from_chrono.date, entry.date);
+        assert_eq!(from_chrono.time, entry.time);
+        assert_eq!(from_chrono.msg, entry.msg);
+
+        assert_eq!(Date { day: 30, month: February, year: 2018 }.to_naive_date(), None);
+        assert_eq!(Date::from_naive_date(&date), entry.date);
+        let before = NaiveDate::from_ymd_opt(-1, 12, 31).expect("date");
+        assert_eq!(Date::from_naive_date(&before), None);
+        assert!(Entry::from_chrono(&before.and_time(time), "Too early").is_none());
+        let after = NaiveDate::from_ymd_opt(65536, 1, 1).expect("date");
+        assert_eq!(Date::from_naive_date(&after), None);
+        let entry = Parser::new("REM Dec 25 2018 MSG All day", 1).entry().expect("entry");
+        assert_eq!(entry.to_naive_datetime(),
+            NaiveDate::from_ymd_opt(2018, 12, 25).and_then(|date| date.and_hms_opt(0, 0, 0)));
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize("REM Mar 30 2018  at 19:00 DURATION 1h15 MSG Event name");
+        assert_eq!(tokens, vec![
+            Token::Keyword("REM"),
+            Token::Word("Mar"),
+            Token::Number("30"),
+            Token::Number("2018"),
+            Token::Keyword("at"),
+            Token::TimeLiteral("19:00"),
+            Token::Keyword("DURATION"),
+            Token::Word("1h15"),
+            Token::Keyword("MSG"),
+            Token::Word("Event"),
+            Token::Word("name"),
+        ]);
+        assert_eq!(tokens[1].text(), "Mar");
+        assert_eq!(tokenize("19: :30 1:2:3"), vec![
+            Token::Word("19:"),
+            Token::Word(":30"),
+            Token::TimeLiteral("1:2:3"),
+        ]);
+        assert!(tokenize(" \t ").is_empty());
+    }
+
+    #[test]
+    fn error_span() {
+        let line = "REM Foo 30 2018 AT 19:00 MSG Event name";
+        let error = Parser::new(line, 1).entry().expect_err("error");
+        assert_eq!(error.span, Some(4..7));
+        assert_eq!(error.render(line), "REM Foo 30 2018 AT 19:00 MSG Event name\n    ^^^ Invalid month foo");
+
+        let line = "REM Feb 30 2018 AT 19:00 MSG Event name";
+        let error = Parser::new(line, 1).entry().expect_err("error");
+        assert_eq!(error.span, Some(4..15));
+
+        let line = "REM Mar 30 2018 AT 7:00xm MSG Event name";
+        let error = Parser::new(line, 1).entry().expect_err("error");
+        assert_eq!(error.span, Some(19..25));
+
+        let line = "REM Mar 30 2018 AT";
+        let error = Parser::new(line, 1).entry().expect_err("error");
+        assert_eq!(error.span, Some(18..18));
+        assert_eq!(error.render(line), "REM Mar 30 2018 AT\n                  ^ Unexpected end of line");
+
+        let line = "REM\tMar 30 2018 19:00 MSG Event name";
+        let error = Parser::new(line, 1).entry().expect_err("error");
+        assert_eq!(error.span, Some(16..21));
+        assert_eq!(error.render(line).lines().nth(1), Some("   \t            ^^^^^ Expecting MSG, found 19:00"));
+
+        let error = ParseError::new(ErrorKind::UnexpectedEndOfLine, 1);
+        assert_eq!(error.render("REM"), "REM\nUnexpected end of line");
+    }
+
+    #[test]
+    fn parse_french_keywords() {
+        let keywords = Keywords {
+            at: "À".to_string(),
+            duration: "DURÉE".to_string(),
+            msg: "MSG".to_string(),
+            omit: "SAUF".to_string(),
+            priority: "PRIORITÉ".to_string(),
+            rem: "RAPPEL".to_string(),
+            skip: "SAUTER".to_string(),
+            tag: "ÉTIQUETTE".to_string(),
+            until: "JUSQUE".to_string(),
+        };
+        let data = "RAPPEL Mar 30 2018 à 19:00 durée 1:15 MSG Événement à venir
+        REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let entries = parse_with_keywords(data.as_bytes(), &keywords).expect("entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entries[0].time, Some(Time { hour: 19, minute: 0, second: 0 }));
+        assert_eq!(entries[0].duration, Some(Duration::from_secs(75 * 60)));
+        assert_eq!(entries[0].msg, "Événement à venir".to_string());
+
+        let entries = parse_with_keywords(data.as_bytes(), &Keywords::default()).expect("entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].msg, "Event name".to_string());
+    }
+
+    #[test]
+    fn parse_french_months() {
+        let data = "REM fév 14 2018 MSG Saint-Valentin
+        REM DÉC 25 2018 MSG Noël
+        REM août 15 2018 MSG Assomption
+        REM 5 1 2018 MSG Premier mai
+        REM Feb 14 2018 MSG Valentine's Day";
+        let entries = parse_with_locale(data.as_bytes(), &Keywords::default(), Locale::French).expect("entries");
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].date, Some(Date { day: 14, month: February, year: 2018 }));
+        assert_eq!(entries[1].date, Some(Date { day: 25, month: December, year: 2018 }));
+        assert_eq!(entries[2].date, Some(Date { day: 15, month: August, year: 2018 }));
+        assert_eq!(entries[3].date, Some(Date { day: 1, month: May, year: 2018 }));
+
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].msg, "Valentine's Day".to_string());
+    }
+
+    #[test]
+    fn parse_crlf() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Event\r\n\r\nREM Mar 31 2018 MSG All day\r\r\n";
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].msg, "Event".to_string());
+        assert_eq!(entries[1].msg, "All day".to_string());
+
+        let error = parse_strict("REM Mar 30 2018 AT\r\n".as_bytes()).expect_err("error");
+        assert_eq!(error.span, Some(18..18));
+    }
+
+    #[test]
+    fn parse_bom() {
+        let data = "\u{feff}REM Mar 30 2018 AT 19:00 MSG Event\nREM Mar 31 2018 MSG All day";
+        assert_eq!(&data.as_bytes()[..3], &[0xEF, 0xBB, 0xBF]);
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(parse_str(data).expect("entries"), entries);
+    }
+
+    #[test]
+    fn parse_weekly() {
+        let line = "REM Mon AT 09:00 MSG Standup";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.date, None);
+        assert_eq!(entry.recurrence, Some(Recurrence::Weekly(Monday)));
+        assert_eq!(entry.time, Some(Time { hour: 9, minute: 0, second: 0 }));
+        assert_eq!(entry.end(), None);
+        assert_eq!(entry.to_string(), line);
+
+        let entry = Parser::new("REM sunday MSG Rest", 1).entry().expect("entry");
+        assert_eq!(entry.recurrence, Some(Recurrence::Weekly(Sunday)));
+    }
+
+    #[test]
+    fn parse_monthly() {
+        let line = "REM * 31 AT 09:00 MSG Pay rent";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.date, None);
+        assert_eq!(entry.recurrence, Some(Recurrence::Monthly { day: 31 }));
+        assert_eq!(entry.to_string(), line);
+
+        match Parser::new("REM * 32 MSG Pay rent", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidDay(32)) => (),
+            result => panic!("Expecting invalid day, found {:?}", result),
+        }
+        match Parser::new("REM * AT 09:00 MSG Pay rent", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidNumber(_)) => (),
+            result => panic!("Expecting invalid number, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_until() {
+        let line = "REM Mon AT 09:00 DURATION 0:15 UNTIL Dec 31 2018 MSG Standup";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.until, Some(Date { day: 31, month: December, year: 2018 }));
+        assert_eq!(entry.to_string(), line);
+
+        let entry = Parser::new("REM * 1 UNTIL Jun 1 2018 MSG Pay rent", 1).entry().expect("entry");
+        assert_eq!(entry.until, Some(Date { day: 1, month: June, year: 2018 }));
+        assert_eq!(Parser::new("REM Mon MSG Standup", 1).entry().expect("entry").until, None);
+
+        match Parser::new("REM Mon UNTIL Feb 30 2018 MSG Standup", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidDate(_)) => (),
+            result => panic!("Expecting invalid date, found {:?}", result),
+        }
+        match Parser::new("REM Mon MSG Standup UNTIL Dec 31 2018", 1).entry() {
+            Ok(entry) => assert_eq!(entry.msg, "Standup UNTIL Dec 31 2018"),
+            result => panic!("Expecting entry, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_advance() {
+        let line = "REM Mar 30 2018 +3 AT 19:00 MSG Event";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.advance_days, 3);
+        assert_eq!(entry.time, Some(Time { hour: 19, minute: 0, second: 0 }));
+        assert_eq!(entry.to_string(), line);
+
+        let entry = Parser::new("REM Mon +1 MSG Standup", 1).entry().expect("entry");
+        assert_eq!(entry.advance_days, 1);
+        assert_eq!(Parser::new("REM Mar 30 2018 MSG Event", 1).entry().expect("entry").advance_days, 0);
+
+        match Parser::new("REM Mar 30 2018 +three MSG Event", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidNumber(_)) => (),
+            result => panic!("Expecting invalid number, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_priority() {
+        let line = "REM Mar 30 2018 AT 19:00 PRIORITY 9999 MSG Event";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.priority, Some(9999));
+        assert_eq!(entry.to_string(), line);
+        assert_eq!(Parser::new("REM Mar 30 2018 MSG Event", 1).entry().expect("entry").priority, None);
+
+        match Parser::new("REM Mar 30 2018 PRIORITY 10000 MSG Event", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidPriority(10000)) => (),
+            result => panic!("Expecting invalid priority, found {:?}", result),
+        }
+        match Parser::new("REM Mar 30 2018 PRIORITY -1 MSG Event", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidNumber(_)) => (),
+            result => panic!("Expecting invalid number, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_tags() {
+        let line = "REM Mar 30 2018 AT 19:00 TAG work TAG Urgent MSG Event";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.tags, vec!["work".to_string(), "Urgent".to_string()]);
+        assert_eq!(entry.to_string(), line);
+        assert!(Parser::new("REM Mar 30 2018 MSG Event", 1).entry().expect("entry").tags.is_empty());
+
+        match Parser::new("REM Mar 30 2018 TAG", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::UnexpectedEndOfLine) => (),
+            result => panic!("Expecting unexpected end of line, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn duration_as_time() {
+        let entry = Parser::new("REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name", 1).entry().expect("entry");
+        assert_eq!(entry.duration_as_time(), Some(Time { hour: 1, minute: 15, second: 0 }));
+        let entry = Parser::new("REM Mar 30 2018 DURATION 25:00:30 MSG Event name", 1).entry().expect("entry");
+        assert_eq!(entry.duration_as_time(), Some(Time { hour: 25, minute: 0, second: 30 }));
+        let entry = Parser::new("REM Mar 30 2018 DURATION 90m MSG Event name", 1).entry().expect("entry");
+        assert_eq!(entry.duration_as_time(), Some(Time { hour: 1, minute: 30, second: 0 }));
+        let entry = Parser::new("REM Mar 30 2018 MSG Event name", 1).entry().expect("entry");
+        assert_eq!(entry.duration_as_time(), Some(Time { hour: 0, minute: 0, second: 0 }));
+        let entry = Parser::new("REM Mar 30 2018 DURATION 300:00 MSG Event name", 1).entry().expect("entry");
+        assert_eq!(entry.duration_as_time(), None);
+    }
+
+    #[test]
+    fn negative_duration() {
+        for line in &["REM Mar 30 2018 DURATION -1:00 MSG Event", "REM Mar 30 2018 DURATION -30m MSG Event"] {
+            let error = Parser::new(line, 1).entry().expect_err("error");
+            assert_eq!(error.span, Some(25..line.find(" MSG").expect("msg")));
+            match error.kind {
+                ErrorKind::NegativeDuration(duration) => assert!(duration.starts_with('-')),
+                kind => panic!("Expecting negative duration, found {:?}", kind),
+            }
+        }
+
+        let entry = Parser::new("REM Mar 30 2018 AT 19:00 DURATION 0:00 MSG Event", 1).entry().expect("entry");
+        assert_eq!(entry.duration, Some(Duration::from_secs(0)));
+        let end = (Date { day: 30, month: March, year: 2018 }, Time { hour: 19, minute: 0, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+    }
+
+    #[test]
+    fn clause_order() {
+        let entry = Parser::new("REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name", 1).entry().expect("entry");
+        let other = Parser::new("REM Mar 30 2018 DURATION 1:15 AT 19:00 MSG Event name", 1).entry().expect("entry");
+        assert_eq!(entry, other);
+        assert_eq!(other.to_string(), "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name");
+
+        let line = "REM Mon TAG work PRIORITY 3 UNTIL Dec 31 2018 DURATION 0:15 TAG daily AT 09:00 MSG Standup";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        let other = Parser::new(&entry.to_string(), 1).entry().expect("entry");
+        assert_eq!(entry, other);
+        assert_eq!(entry.tags, vec!["work".to_string(), "daily".to_string()]);
+    }
+
+    #[test]
+    fn optional_message() {
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.msg, "");
+        assert_eq!(entry.duration, Some(Duration::from_secs(75 * 60)));
+        assert_eq!(entry.to_string(), line);
+
+        let entry = Parser::new("REM Mar 30 2018 MSG", 1).entry().expect("entry");
+        assert_eq!(entry.msg, "");
+        assert_eq!(entry.to_string(), "REM Mar 30 2018");
+    }
+
+    #[test]
+    fn entry_builder() {
+        let date = Date { day: 30, month: March, year: 2018 };
+        let entry = EntryBuilder::new(date, Time { hour: 19, minute: 0, second: 0 })
+            .duration(Duration::from_secs(75 * 60))
+            .message("Event name")
+            .build()
+            .expect("entry");
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        assert_eq!(entry, Parser::new(line, 1).entry().expect("entry"));
+
+        let entry = EntryBuilder::new(date, Time { hour: 7, minute: 5, second: 0 })
+            .build()
+            .expect("entry");
+        assert_eq!(entry.to_string(), "REM Mar 30 2018 AT 07:05");
+
+        let time = Time { hour: 7, minute: 0, second: 0 };
+        match EntryBuilder::new(Date { day: 29, month: February, year: 2018 }, time).build() {
+            Err(ErrorKind::InvalidDate(_)) => (),
+            result => panic!("Expecting invalid date, found {:?}", result),
+        }
+        match EntryBuilder::new(date, Time { hour: 7, minute: 60, second: 0 }).build() {
+            Err(ErrorKind::InvalidMinute(60)) => (),
+            result => panic!("Expecting invalid minute, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn entry_from_str() {
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event";
+        let entry = line.parse::<Entry>().expect("entry");
+        assert_eq!(entry, Parser::new(line, 1).entry().expect("entry"));
+
+        match "REM Mar 32 2018 MSG Event".parse::<Entry>() {
+            Err(ParseError { kind: ErrorKind::InvalidDate(_), line: 1, .. }) => (),
+            result => panic!("Expecting invalid date, found {:?}", result),
+        }
+
+        let data = "REM Mar 30 2018 MSG First\nREM Mar 31 2018 MSG Second";
+        let entries = data.lines().map(str::parse).collect::<Result<Vec<Entry>, _>>().expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert!(data.lines().chain(Some("Event")).map(str::parse::<Entry>).collect::<Result<Vec<_>, _>>().is_err());
+    }
+
+    #[test]
+    fn parser_parts() {
+        let mut parser = Parser::new("Mar 30 2018 DURATION 1:15 AT 7pm MSG Event name", 1);
+        assert_eq!(parser.date().expect("date"), Date { day: 30, month: March, year: 2018 });
+        match parser.time().map_err(|error| error.kind) {
+            Err(ErrorKind::ExpectedKeyword { expected, .. }) => assert_eq!(expected, "AT"),
+            result => panic!("Expecting keyword, found {:?}", result),
+        }
+        assert_eq!(parser.duration().expect("duration"), Duration::from_secs(75 * 60));
+        assert_eq!(parser.time().expect("time"), Time { hour: 19, minute: 0, second: 0 });
+        assert_eq!(parser.message().expect("message"), "Event name");
+        match parser.message().map_err(|error| error.kind) {
+            Err(ErrorKind::ExpectedKeyword { found: None, .. }) => (),
+            result => panic!("Expecting keyword, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn succ_pred() {
+        let dates = [
+            (Date { day: 30, month: March, year: 2018 }, Date { day: 31, month: March, year: 2018 }),
+            (Date { day: 31, month: March, year: 2018 }, Date { day: 1, month: April, year: 2018 }),
+            (Date { day: 28, month: February, year: 2018 }, Date { day: 1, month: March, year: 2018 }),
+            (Date { day: 28, month: February, year: 2020 }, Date { day: 29, month: February, year: 2020 }),
+            (Date { day: 29, month: February, year: 2020 }, Date { day: 1, month: March, year: 2020 }),
+            (Date { day: 31, month: December, year: 2018 }, Date { day: 1, month: January, year: 2019 }),
+        ];
+        for &(date, next) in &dates {
+            assert_eq!(date.checked_succ(), Some(next));
+            assert_eq!(next.checked_pred(), Some(date));
+        }
+        assert_eq!(Date::MIN.checked_pred(), None);
+        assert_eq!(Date::MAX.checked_succ(), None);
+        assert_eq!(Date { day: 30, month: December, year: 65535 }.checked_succ(), Some(Date::MAX));
+        assert_eq!(Date { day: 2, month: January, year: 0 }.checked_pred(), Some(Date::MIN));
+    }
+
+    #[test]
+    fn add_days() {
+        let date = Date { day: 30, month: March, year: 2018 };
+        assert_eq!(date.add_days(0), Some(date));
+        assert_eq!(date.add_days(2), Some(Date { day: 1, month: April, year: 2018 }));
+        assert_eq!(date.add_days(-30), Some(Date { day: 28, month: February, year: 2018 }));
+        assert_eq!(date.add_days(-365), Some(Date { day: 30, month: March, year: 2017 }));
+
+        let date = Date { day: 20, month: February, year: 2020 };
+        assert_eq!(date.add_days(10), Some(Date { day: 1, month: March, year: 2020 }));
+        assert_eq!(date.add_days(10).and_then(|date| date.add_days(-1)),
+            Some(Date { day: 29, month: February, year: 2020 }));
+        assert_eq!(date.add_days(366), Some(Date { day: 20, month: February, year: 2021 }));
+
+        let date = Date { day: 25, month: December, year: 2018 };
+        assert_eq!(date.add_days(10), Some(Date { day: 4, month: January, year: 2019 }));
+        assert_eq!(date.add_days(10).and_then(|date| date.add_days(-10)), Some(date));
+
+        assert_eq!(Date::MIN.add_days(-1), None);
+        assert_eq!(Date::MIN.add_days(0), Some(Date::MIN));
+        assert_eq!(Date::MAX.add_days(1), None);
+        assert_eq!(Date::MAX.add_days(-365), Some(Date { day: 31, month: December, year: 65534 }));
+        assert_eq!(Date::MIN.add_days(i32::MAX), None);
+        assert_eq!(Date::MAX.add_days(i32::MIN), None);
+    }
+
+    #[test]
+    fn two_digit_year() {
+        let entry = Parser::new("REM Mar 30 18 AT 19:00 MSG Event", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 30, month: March, year: 2018 }));
+        let entry = Parser::new("REM Dec 31 99 MSG Party", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 31, month: December, year: 1999 }));
+        let entry = Parser::new("REM Jan 1 0 MSG Millennium", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 1, month: January, year: 2000 }));
+        let entry = Parser::new("REM Jan 1 70 MSG Epoch", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 1, month: January, year: 1970 }));
+        let entry = Parser::new("REM Jan 1 69 MSG Before the pivot", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 1, month: January, year: 2069 }));
+
+        let entry = Parser::new("REM Mar 30 0018 MSG Literal", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 30, month: March, year: 18 }));
+        assert_eq!(entry.to_string(), "REM Mar 30 0018 MSG Literal");
+        let line = entry.to_string();
+        assert_eq!(Parser::new(&line, 1).entry().expect("entry"), entry);
+        let entry = Parser::new("REM Mar 30 2018 MSG Event", 1).entry().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 30, month: March, year: 2018 }));
+    }
+
+    #[test]
+    fn weekend() {
+        let saturday = Date { day: 31, month: March, year: 2018 };
+        assert!(saturday.is_weekend());
+        assert!(!saturday.is_weekday());
+        assert!(saturday.checked_succ().is_some_and(|date| date.is_weekend()));
+        let monday = saturday.add_days(2).expect("date");
+        assert!(monday.is_weekday());
+        assert!(!monday.is_weekend());
+        let friday = Date { day: 30, month: March, year: 2018 };
+        assert!(friday.is_weekday());
+        assert!(Date { day: 1, month: January, year: 2000 }.is_weekend());
+        assert!(Date { day: 14, month: October, year: 2026 }.is_weekday());
+    }
+
+    #[test]
+    fn parse_omit() {
+        let line = "REM Mon AT 09:00 OMIT Apr 2 2018 OMIT Dec 24 2018 MSG Standup";
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.omit, vec![
+            Date { day: 2, month: April, year: 2018 },
+            Date { day: 24, month: December, year: 2018 },
+        ]);
+        assert_eq!(entry.to_string(), line);
+        assert!(Parser::new("REM Mon MSG Standup", 1).entry().expect("entry").omit.is_empty());
+    }
+
+    #[test]
+    fn parse_several() {
+        let first = "REM Mar 31 2018 MSG First file, later
+        REM Mar 30 2018 AT 19:00 MSG First file, evening";
+        let second = "REM Mar 30 2018 AT 08:00 MSG Second file, morning
+        Not an entry
+        REM Mar 30 2018 AT 19:00 MSG Second file, evening";
+        let entries = parse_many(vec![first.as_bytes(), second.as_bytes()]).expect("entries");
+        let messages: Vec<_> = entries.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec![
+            "Second file, morning",
+            "First file, evening",
+            "Second file, evening",
+            "First file, later",
+        ]);
+
+        let invalid: &[u8] = b"REM Mar 30 2018 MSG Valid\n\xFF";
+        match parse_many(vec![first.as_bytes(), invalid]) {
+            Err(error) => {
+                assert_eq!(error.reader, Some(1));
+                assert_eq!(error.line, 2);
+                assert!(error.to_string().starts_with("reader 1, line 2: "));
+            },
+            result => panic!("Expecting I/O error, found {:?}", result),
+        }
+
+        let first = "REM Mar 30 2018 AT 19:00 MSG Zed";
+        let second = "REM Mar 30 2018 AT 19:00 MSG Alpha";
+        let entries = parse_many(vec![first.as_bytes(), second.as_bytes()]).expect("entries");
+        let messages: Vec<_> = entries.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Zed", "Alpha"]);
+    }
+
+    #[test]
+    fn quoted_message() {
+        let line = r#"REM Mar 30 2018 AT 19:00 MSG "  indented \"text\" with \ backslash  "  "#;
+        let entry = Parser::new(line, 1).entry().expect("entry");
+        assert_eq!(entry.msg, r#"  indented "text" with \ backslash  "#);
+        assert_eq!(Parser::new(&entry.to_string(), 1).entry().expect("entry"), entry);
+
+        let entry = Parser::new(r#"REM Mar 30 2018 MSG "\"Quoted\" at the start""#, 1).entry().expect("entry");
+        assert_eq!(entry.msg, r#""Quoted" at the start"#);
+        assert_eq!(entry.to_string(), r#"REM Mar 30 2018 MSG "\"Quoted\" at the start""#);
+        let entry = Parser::new(r#"REM Mar 30 2018 MSG Not "quoted""#, 1).entry().expect("entry");
+        assert_eq!(entry.msg, r#"Not "quoted""#);
+        assert_eq!(entry.to_string(), r#"REM Mar 30 2018 MSG Not "quoted""#);
+        let entry = Parser::new(r#"REM Mar 30 2018 MSG "C:\\Users\\""#, 1).entry().expect("entry");
+        assert_eq!(entry.msg, r"C:\Users\");
+        assert_eq!(entry.to_string(), r#"REM Mar 30 2018 MSG "C:\\Users\\""#);
+
+        // Without a closing quote ending it, the message is taken verbatim.
+        for &message in &[r#""Unterminated"#, r#""Hamlet" tonight"#] {
+            let entry = Parser::new(&format!("REM Mar 30 2018 MSG {}", message), 1).entry().expect("entry");
+            assert_eq!(entry.msg, message);
+            assert_eq!(Parser::new(&entry.to_string(), 1).entry().expect("entry"), entry);
+        }
+    }
+
+    #[test]
+    fn cross_midnight() {
+        let data = "REM Mar 30 2018 AT 23:00 DURATION 3:00 MSG Late
+        REM Mar 30 2018 AT 23:00 DURATION 1:00 MSG Until midnight
+        REM Mar 30 2018 DURATION 25:00 MSG Long all day
+        REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Evening";
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 4);
+        let end = (Date { day: 31, month: March, year: 2018 }, Time { hour: 2, minute: 0, second: 0 });
+        assert_eq!(entries[0].end(), Some(end));
+
+        let options = ParseOptions::default().reject_cross_midnight(true);
+        let entries = parse_with_options(data.as_bytes(), &options).expect("entries");
+        let messages: Vec<_> = entries.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Until midnight", "Evening"]);
+
+        let line = "REM Mar 30 2018 AT 23:00 DURATION 3:00 MSG Late";
+        let error = Parser::with_options(line, 1, &options).entry().expect_err("error");
+        assert_eq!(error.span, Some(34..38));
+        match error.kind {
+            ErrorKind::DurationCrossesMidnight => (),
+            kind => panic!("Expecting duration crossing midnight, found {:?}", kind),
+        }
+
+        let line = "REM Mar 30 2018 AT 23:59 DURATION 5124095576030431:00 MSG Huge";
+        let error = Parser::with_options(line, 1, &options).entry().expect_err("error");
+        match error.kind {
+            ErrorKind::DurationCrossesMidnight => (),
+            kind => panic!("Expecting duration crossing midnight, found {:?}", kind),
+        }
+
+        let options = options.default_duration(Some(Duration::from_secs(3 * 60 * 60)));
+        let line = "REM Mar 30 2018 AT 23:00 MSG Late";
+        let error = Parser::with_options(line, 1, &options).entry().expect_err("error");
+        assert_eq!(error.span, Some(16..18));
+        match error.kind {
+            ErrorKind::DurationCrossesMidnight => (),
+            kind => panic!("Expecting duration crossing midnight, found {:?}", kind),
+        }
+        let line = "REM Mar 30 2018 AT 20:00 MSG Evening";
+        let entry = Parser::with_options(line, 1, &options).entry().expect("entry");
+        assert_eq!(entry.duration, Some(Duration::from_secs(3 * 60 * 60)));
+        let line = "REM Mar 30 2018 AT 23:00 DURATION 0:30 MSG Short";
+        assert!(Parser::with_options(line, 1, &options).entry().is_ok());
+    }
+
+    #[test]
+    fn parse_options() {
+        let keywords = Keywords {
+            rem: "RAPPEL".to_string(),
+            ..Keywords::default()
+        };
+        let options = ParseOptions::default()
+            .keywords(keywords)
+            .locale(Locale::French);
+        let entries = parse_with_options("RAPPEL mars 30 2018 MSG Event".as_bytes(), &options).expect("entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        let entries = parse_with_options("REM Mar 30 2018 MSG Event".as_bytes(), &options).expect("entries");
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn parse_tabs() {
+        let entry: Entry = "REM\tMar 30 2018\tAT 19:00\tDURATION\t1:15\tMSG\tName\tPlace".parse().expect("entry");
+        assert_eq!(entry.date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entry.time, Some(Time { hour: 19, minute: 0, second: 0 }));
+        assert_eq!(entry.duration, Some(Duration::from_secs(75 * 60)));
+        assert_eq!(entry.msg, "Name\tPlace".to_string());
+    }
+
+    #[test]
+    fn leap_year() {
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+        assert!(is_leap_year(2020));
+        assert!(!is_leap_year(2018));
+    }
+
+    #[test]
+    fn ordinal() {
+        assert_eq!(Date { day: 1, month: January, year: 2018 }.ordinal(), 1);
+        assert_eq!(Date { day: 1, month: March, year: 2018 }.ordinal(), 60);
+        assert_eq!(Date { day: 1, month: March, year: 2020 }.ordinal(), 61);
+        assert_eq!(Date { day: 31, month: December, year: 2018 }.ordinal(), 365);
+        assert_eq!(Date { day: 31, month: December, year: 2020 }.ordinal(), 366);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn parse_async() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Event
+        Invalid line
+        REM Mar 31 2018 MSG Other \\
+            event";
+        let runtime = tokio::runtime::Builder::new_current_thread().build().expect("runtime");
+        let entries = runtime.block_on(super::parse_async(data.as_bytes())).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].msg, "Event".to_string());
+        assert_eq!(entries[1].date, Some(Date { day: 31, month: March, year: 2018 }));
+        assert_eq!(entries[1].msg, "Other event".to_string());
+    }
+
+    #[test]
+    fn borrowed_tokens() {
+        let line = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name";
+        let range = line.as_ptr() as usize..line.as_ptr() as usize + line.len();
+        for token in tokenize(line) {
+            assert!(range.contains(&(token.text().as_ptr() as usize)));
+        }
+        let entry: Entry = line.parse().expect("entry");
+        assert_eq!(entry.to_string(), line);
+    }
+
+    #[test]
+    fn parse_capacity() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Event
+        REM Mar 31 2018 MSG Other event";
+        let entries = parse_with_capacity(data.as_bytes(), 100).expect("entries");
+        assert!(entries.capacity() >= 100);
+        assert_eq!(entries, parse(data.as_bytes()).expect("entries"));
+        assert_eq!(entries, parse_with_capacity(data.as_bytes(), 0).expect("entries"));
+    }
+
+    #[test]
+    fn same_slot() {
+        let entry: Entry = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event".parse().expect("entry");
+        let other: Entry = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Other event".parse().expect("entry");
+        assert!(entry.same_slot(&other));
+        assert_ne!(entry, other);
+        let later: Entry = "REM Mar 30 2018 AT 20:00 DURATION 1:15 MSG Event".parse().expect("entry");
+        assert!(!entry.same_slot(&later));
+    }
+
+    #[test]
+    fn continued_message() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Long message \\
+            continuing here
+        REM Mar 31 2018 MSG Other event";
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].msg, "Long message continuing here".to_string());
+        assert_eq!(entries[1].msg, "Other event".to_string());
+        assert_eq!(parse_str(data).expect("entries"), entries);
+        let error = parse_strict("REM Mar 30 2018 AT \\\n19:00 MSG Event".as_bytes()).expect_err("error");
+        assert_eq!(error.line, 1);
+        let data = "# old: REM Mar 29 2018 MSG see \\\nREM Mar 30 2018 MSG Real";
+        let entries = parse_strict(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].msg, "Real".to_string());
+        assert_eq!(parse_str(data).expect("entries"), entries);
+    }
+
+    #[test]
+    fn duplicate_keyword() {
+        let error = Parser::new("REM Mar 30 2018 AT 19:00 AT 20:00 MSG X", 1).entry().expect_err("error");
+        assert_eq!(error.span, Some(25..27));
+        match error.kind {
+            ErrorKind::DuplicateKeyword(ref keyword) => assert_eq!(keyword, "AT"),
+            kind => panic!("Expecting duplicate keyword, found {:?}", kind),
+        }
+        let entry: Entry = "REM Mar 30 2018 TAG work TAG urgent MSG X".parse().expect("entry");
+        assert_eq!(entry.tags.len(), 2);
+    }
+
+    #[test]
+    fn minutes_since_midnight() {
+        assert_eq!(Time { hour: 0, minute: 0, second: 0 }.minutes_since_midnight(), 0);
+        assert_eq!(Time { hour: 12, minute: 30, second: 15 }.minutes_since_midnight(), 750);
+        assert_eq!(Time { hour: 23, minute: 59, second: 0 }.minutes_since_midnight(), 1439);
+        assert_eq!(Time::from_minutes_since_midnight(0), Time { hour: 0, minute: 0, second: 0 });
+        assert_eq!(Time::from_minutes_since_midnight(750), Time { hour: 12, minute: 30, second: 0 });
+        assert_eq!(Time::from_minutes_since_midnight(1439), Time { hour: 23, minute: 59, second: 0 });
+        assert_eq!(Time::from_minutes_since_midnight(1440 + 90), Time { hour: 1, minute: 30, second: 0 });
+    }
+
+    #[test]
+    fn noon_midnight() {
+        let entry: Entry = "REM Mar 30 2018 AT noon MSG Lunch".parse().expect("entry");
+        assert_eq!(entry.time, Some(Time { hour: 12, minute: 0, second: 0 }));
+        let entry: Entry = "REM Mar 30 2018 AT Midnight MSG New day".parse().expect("entry");
+        assert_eq!(entry.time, Some(Time { hour: 0, minute: 0, second: 0 }));
+        let entry: Entry = "REM Mar 30 2018 AT 12:00 MSG Lunch".parse().expect("entry");
+        assert_eq!(entry.time, Some(Time { hour: 12, minute: 0, second: 0 }));
+        match Parser::new("REM Mar 30 2018 DURATION noon MSG Lunch", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidDuration(ref duration)) => assert_eq!(duration, "noon"),
+            result => panic!("Expecting invalid duration, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn all_day() {
+        let data = "REM Mar 30 2018 AT 09:00 MSG Meeting
+        REM Mar 30 2018 MSG Holiday
+        REM Mar 29 2018 AT 19:00 MSG Dinner";
+        let mut entries = parse_str(data).expect("entries");
+        assert!(!entries[0].is_all_day());
+        assert!(entries[1].is_all_day());
+        entries.sort();
+        let messages: Vec<_> = entries.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Dinner", "Holiday", "Meeting"]);
+        let date = Date { day: 30, month: March, year: 2018 };
+        let messages: Vec<_> = entries_on(&entries, &date).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Holiday", "Meeting"]);
+    }
+
+    #[test]
+    fn default_duration() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Event
+        REM Mar 31 2018 AT 19:00 DURATION 0:30 MSG Short event";
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries[0].duration, None);
+        assert_eq!(entries[1].duration, Some(Duration::from_secs(30 * 60)));
+
+        let options = ParseOptions::default().default_duration(Some(Duration::from_secs(60 * 60)));
+        let entries = parse_with_options(data.as_bytes(), &options).expect("entries");
+        assert_eq!(entries[0].duration, Some(Duration::from_secs(60 * 60)));
+        assert_eq!(entries[1].duration, Some(Duration::from_secs(30 * 60)));
+    }
+
+    #[test]
+    fn march_spellings() {
+        let expected = Date { day: 30, month: March, year: 2018 };
+        for month in &["Mar", "mar", "MAR", "March", "march", "MARCH", "3", "03"] {
+            let entry: Entry = format!("REM {} 30 2018 MSG Event", month).parse().expect("entry");
+            assert_eq!(entry.date, Some(expected), "month written {}", month);
+        }
+        let options = ParseOptions::default().locale(Locale::French);
+        for month in &["mars", "MARS", "mar", "3"] {
+            let line = format!("REM {} 30 2018 MSG Event", month);
+            let entry = Parser::with_options(&line, 1, &options).entry().expect("entry");
+            assert_eq!(entry.date, Some(expected), "month written {}", month);
+        }
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(&Duration::from_secs(45 * 60)), "0:45");
+        assert_eq!(format_duration(&Duration::from_secs(75 * 60)), "1:15");
+        assert_eq!(format_duration(&Duration::from_secs(10 * 60 * 60)), "10:00");
+        assert_eq!(format_duration(&Duration::from_secs(26 * 60 * 60)), "26:00");
+        assert_eq!(format_duration(&Duration::from_secs(90 * 60 + 30)), "1:30:30");
+    }
+
+    #[test]
+    fn month_prefixes() {
+        assert_eq!("sept".parse::<Month>().expect("month"), September);
+        assert_eq!("Septemb".parse::<Month>().expect("month"), September);
+        assert_eq!("june".parse::<Month>().expect("month"), June);
+        assert_eq!("JUL".parse::<Month>().expect("month"), July);
+        for word in &["ju", "se", ""] {
+            match word.parse::<Month>() {
+                Err(ErrorKind::InvalidMonth(ref month)) => assert_eq!(month, word),
+                result => panic!("Expecting invalid month, found {:?}", result),
+            }
+        }
+        let options = ParseOptions::default().locale(Locale::French);
+        match Parser::with_options("REM jui 14 2018 MSG Event", 1, &options).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::AmbiguousMonth(month)) => assert_eq!(month, "jui"),
+            result => panic!("Expecting ambiguous month, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_spans() {
+        let data = concat!(
+            "REM Mar 30 2018 AT 19:00 MSG Event\r\n",
+            "REM Mar 31 2018 MSG Other event\n",
+            "\n",
+            "REM Apr 1 2018 MSG Long \\\n  event",
+        );
+        let entries = parse_with_spans(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].1, 0..34);
+        assert_eq!(entries[1].0.msg, "Other event".to_string());
+        assert_eq!(entries[1].1, 36..67);
+        assert_eq!(&data[entries[1].1.clone()], "REM Mar 31 2018 MSG Other event");
+        assert_eq!(entries[2].0.msg, "Long event".to_string());
+        assert_eq!(&data[entries[2].1.clone()], "REM Apr 1 2018 MSG Long \\\n  event");
+    }
+
+    #[test]
+    fn inline_comments() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Event # needs confirmation
+        REM Mar 31 2018 MSG \"Meeting # 3\" # in room 2
+        REM Apr 1 2018 MSG Issue#42";
+        let entries = parse(data.as_bytes()).expect("entries");
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].msg, "Event # needs confirmation".to_string());
+        assert_eq!(entries[1].msg, "\"Meeting # 3\" # in room 2".to_string());
+        assert_eq!(entries[2].msg, "Issue#42".to_string());
+
+        let options = ParseOptions::default().inline_comments(true);
+        let entries = parse_with_options(data.as_bytes(), &options).expect("entries");
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].msg, "Event".to_string());
+        assert_eq!(entries[1].msg, "Meeting # 3".to_string());
+        assert_eq!(entries[2].msg, "Issue#42".to_string());
+    }
+
+    #[test]
+    fn contains_time() {
+        let entry: Entry = "REM Mar 30 2018 AT 14:00 DURATION 1:00 MSG Event".parse().expect("entry");
+        assert!(entry.contains_time(&Time { hour: 14, minute: 0, second: 0 }));
+        assert!(entry.contains_time(&Time { hour: 14, minute: 30, second: 0 }));
+        assert!(!entry.contains_time(&Time { hour: 15, minute: 0, second: 0 }));
+        assert!(!entry.contains_time(&Time { hour: 9, minute: 0, second: 0 }));
+
+        let entry: Entry = "REM Mar 30 2018 AT 23:00 DURATION 2:00 MSG Late".parse().expect("entry");
+        assert!(entry.contains_time(&Time { hour: 23, minute: 30, second: 0 }));
+        assert!(!entry.contains_time(&Time { hour: 0, minute: 30, second: 0 }));
+
+        let entry: Entry = "REM Mar 30 2018 MSG Holiday".parse().expect("entry");
+        assert!(entry.contains_time(&Time { hour: 0, minute: 0, second: 0 }));
+        assert!(entry.contains_time(&Time { hour: 23, minute: 59, second: 59 }));
+        let end = (Date { day: 31, month: March, year: 2018 }, Time { hour: 0, minute: 0, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+    }
+
+    #[test]
+    fn line_too_long() {
+        let line = "REM Mar 30 2018 MSG Event";
+        let options = ParseOptions::default().max_line_bytes(Some(line.len()));
+        let data = format!("{}\n{}!", line, line);
+        let mut reader = EntryReader::with_options(data.as_bytes(), options);
+        assert!(reader.next().expect("entry").is_ok());
+        let error = reader.next().expect("entry").expect_err("error");
+        assert_eq!(error.line, 2);
+        match error.kind {
+            ErrorKind::LineTooLong(length) => assert_eq!(length, line.len() + 1),
+            kind => panic!("Expecting line too long, found {:?}", kind),
+        }
+        assert_eq!(parse(data.as_bytes()).expect("entries").len(), 2);
+    }
+
+    #[test]
+    fn month_name() {
+        let names: Vec<_> = MONTHS.iter()
+            .map(Month::name)
+            .collect();
+        assert_eq!(names, vec!["January", "February", "March", "April", "May", "June", "July", "August", "September",
+            "October", "November", "December"]);
+        for month in &MONTHS {
+            assert_eq!(month.name().parse::<Month>().expect("month"), *month);
+        }
+    }
+
+    #[test]
+    fn iso_dates() {
+        let options = ParseOptions::default().iso_dates(true);
+        let data = "REM 2018-03-30 AT 19:00 MSG Event
+        REM Mar 30 2018 AT 19:00 MSG Event
+        REM 2018-02-30 MSG Invalid";
+        let entries = parse_with_options(data.as_bytes(), &options).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].date, Some(Date { day: 30, month: March, year: 2018 }));
+        assert_eq!(entries[0], entries[1]);
+        match Parser::with_options("REM 2018-13-01 MSG Event", 1, &options).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidMonth(ref month)) => assert_eq!(month, "13"),
+            result => panic!("Expecting invalid month, found {:?}", result),
+        }
+        assert_eq!(parse(data.as_bytes()).expect("entries").len(), 1);
+
+        let date = Date { day: 5, month: January, year: 18 };
+        assert_eq!(format_iso_date(&date), "0018-01-05");
+        assert_eq!(parse_iso_date(&format_iso_date(&date)).expect("date"), date);
+    }
+
+    #[test]
+    fn skip_weekend() {
+        let entry: Entry = "REM Mar 31 2018 SKIP WEEKEND MSG Pay".parse().expect("entry");
+        assert_eq!(entry.skip, SkipRule::Weekend);
+        assert_eq!(entry.to_string(), "REM Mar 31 2018 SKIP WEEKEND MSG Pay");
+        let from = Date { day: 26, month: March, year: 2018 };
+        let to = Date { day: 8, month: April, year: 2018 };
+        let dates: Vec<_> = expand(&entry, &from, &to).iter()
+            .map(|occurrence| occurrence.date.expect("date"))
+            .collect();
+        assert_eq!(dates, vec![Date { day: 2, month: April, year: 2018 }]);
+
+        let entry: Entry = "REM Mar 31 2018 SKIP NONE MSG Pay".parse().expect("entry");
+        assert_eq!(entry.skip, SkipRule::None);
+        assert_eq!(expand(&entry, &from, &to)[0].date, Some(Date { day: 31, month: March, year: 2018 }));
+        match Parser::new("REM Mar 31 2018 SKIP HOLIDAY MSG Pay", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidSkipRule(ref rule)) => assert_eq!(rule, "HOLIDAY"),
+            result => panic!("Expecting invalid skip rule, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parser_position() {
+        let mut parser = Parser::new("Mar 30 2018 AT 19:00 MSG Event", 1);
+        assert_eq!(parser.position(), 0);
+        assert_eq!(parser.date().expect("date"), Date { day: 30, month: March, year: 2018 });
+        assert_eq!(parser.position(), 3);
+        assert_eq!(parser.remaining(), &[Token::Keyword("AT"), Token::TimeLiteral("19:00"), Token::Keyword("MSG"),
+            Token::Word("Event")]);
+        parser.time().expect("time");
+        parser.message().expect("message");
+        assert_eq!(parser.position(), 7);
+        assert!(parser.remaining().is_empty());
+    }
+
+    #[test]
+    fn long_duration() {
+        let entry: Entry = "REM Mar 30 2018 DURATION 48:00 MSG Conference".parse().expect("entry");
+        assert_eq!(entry.duration.map(|duration| duration.as_secs()), Some(48 * 60 * 60));
+        let entry: Entry = "REM Mar 30 2018 DURATION 300:30 MSG Expedition".parse().expect("entry");
+        assert_eq!(entry.duration.map(|duration| duration.as_secs()), Some(300 * 60 * 60 + 30 * 60));
+        assert_eq!(entry.to_string(), "REM Mar 30 2018 DURATION 300:30 MSG Expedition");
+        let end = (Date { day: 11, month: April, year: 2018 }, Time { hour: 12, minute: 30, second: 0 });
+        assert_eq!(entry.end(), Some(end));
+        let entry: Entry = "REM Mar 30 2018 DURATION 999999999:00 MSG Forever".parse().expect("entry");
+        assert_eq!(entry.end(), None);
+        let result = Parser::new("REM Mar 30 2018 DURATION 9999999999999999999:00 MSG x", 1).entry();
+        match result.map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidDuration(ref duration)) => assert_eq!(duration, "9999999999999999999:00"),
+            result => panic!("Expecting invalid duration, found {:?}", result),
+        }
+        match Parser::new("REM Mar 30 2018 AT 48:00 MSG Event", 1).entry().map_err(|error| error.kind) {
+            Err(ErrorKind::InvalidHour(48)) => (),
+            result => panic!("Expecting invalid hour, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn day_number() {
+        let mut date = Date { day: 1, month: January, year: 0 };
+        assert_eq!(Date::from_day_number(date.day_number()), Some(date));
+        for _ in 0..3000 {
+            let next = date.checked_succ().expect("date");
+            assert_eq!(next.day_number(), date.day_number() + 1);
+            assert_eq!(Date::from_day_number(next.day_number()), Some(next));
+            date = next;
+        }
+        let date = Date { day: 31, month: December, year: 65535 };
+        assert_eq!(Date::from_day_number(date.day_number()), Some(date));
+        assert_eq!(Date::from_day_number(date.day_number() + 1), None);
     }
 }
@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use {Entry, format_cells};
+
+/// Export the entries as a GitHub-flavored Markdown table with the columns date, time, duration and message.
+pub fn to_markdown(entries: &[Entry]) -> String {
+    let mut output = String::from("| Date | Time | Duration | Message |\n| --- | --- | --- | --- |\n");
+    for entry in entries {
+        let [date, time, duration] = format_cells(entry);
+        output.push_str(&format!("| {} | {} | {} | {} |\n", date, time, duration, entry.msg.replace('|', "\\|")));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+    use super::to_markdown;
+
+    #[test]
+    fn export_markdown() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event | with pipe
+        REM Apr 9 2018 MSG All day";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(to_markdown(&entries), "\
+| Date | Time | Duration | Message |
+| --- | --- | --- | --- |
+| Mar 30 2018 | 19:00 | 1:15 | Event \\| with pipe |
+| Apr 9 2018 |  |  | All day |
+");
+    }
+}
@@ -0,0 +1,598 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use {Date, Entry, Month, Recurrence, SkipRule, Time, days_in_month};
+use Weekday::{Saturday, Sunday};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Get the entries whose date is between `start` and `end`, inclusively.
+pub fn entries_in_range<'a>(entries: &'a [Entry], start: &Date, end: &Date) -> Vec<&'a Entry> {
+    entries.iter()
+        .filter(|entry| entry.date.is_some_and(|date| date >= *start && date <= *end))
+        .collect()
+}
+
+/// Remove the entries that are equal to a previous one, keeping the order of the others.
+pub fn dedup(entries: Vec<Entry>) -> Vec<Entry> {
+    let mut seen = HashSet::new();
+    let first: Vec<_> = entries.iter()
+        .map(|entry| seen.insert(entry))
+        .collect();
+    entries.into_iter()
+        .zip(first)
+        .filter_map(|(entry, first)| if first { Some(entry) } else { None })
+        .collect()
+}
+
+/// Get the entries scheduled on `date`, sorted by time.
+pub fn entries_on<'a>(entries: &'a [Entry], date: &Date) -> Vec<&'a Entry> {
+    let mut result: Vec<_> = entries.iter()
+        .filter(|entry| entry.date == Some(*date))
+        .collect();
+    result.sort_by_key(|entry| entry.time);
+    result
+}
+
+/// Get the occurrences of the entry between `from` and `to`, inclusively, as entries with a date.
+/// An entry with a date has at most one occurrence and a recurring entry has none after its `until` date nor on its
+/// `omit` dates. The occurrences are moved according to the `skip` rule of the entry.
+pub fn expand(entry: &Entry, from: &Date, to: &Date) -> Vec<Entry> {
+    let occurrence = |date: Date| {
+        if entry.omit.contains(&date) {
+            return None;
+        }
+        let moved =
+            match (entry.skip, date.weekday()) {
+                (SkipRule::Weekend, Saturday) => date.add_days(2),
+                (SkipRule::Weekend, Sunday) => date.add_days(1),
+                _ => Some(date),
+            };
+        // An occurrence moved past the last representable date is dropped.
+        let moved = moved.filter(|moved| moved >= from && moved <= to)?;
+        Some(Entry {
+            advance_days: entry.advance_days,
+            date: Some(moved),
+            duration: entry.duration,
+            msg: entry.msg.clone(),
+            omit: vec![],
+            priority: entry.priority,
+            recurrence: None,
+            skip: SkipRule::None,
+            tags: entry.tags.clone(),
+            time: entry.time,
+            until: None,
+        })
+    };
+    let last = entry.until.map_or(*to, |until| until.min(*to));
+    let recurrence =
+        match entry.recurrence {
+            Some(recurrence) => recurrence,
+            // An entry with a date has a single occurrence, so the range is not visited.
+            None => return entry.date.filter(|date| *date <= last).and_then(occurrence).into_iter().collect(),
+        };
+    let occurs_on = |date: &Date| {
+        match recurrence {
+            Recurrence::Monthly { day } => date.day == day.min(days_in_month(&date.month, date.year)),
+            Recurrence::Weekly(weekday) => date.weekday() == weekday,
+        }
+    };
+    let mut occurrences = vec![];
+    // An occurrence moved by the skip rule can come from the weekend before the range.
+    let mut date =
+        match entry.skip {
+            SkipRule::None => *from,
+            SkipRule::Weekend => from.add_days(-2).unwrap_or(Date::MIN),
+        };
+    while date <= last {
+        if occurs_on(&date) {
+            occurrences.extend(occurrence(date));
+        }
+        match date.checked_succ() {
+            Some(next) => date = next,
+            None => break,
+        }
+    }
+    occurrences
+}
+
+/// Group the entries by year and month, each group being sorted chronologically.
+/// Recurring entries are ignored: use `expand` to get their occurrences.
+pub fn group_by_month(entries: &[Entry]) -> BTreeMap<(u16, Month), Vec<&Entry>> {
+    let mut groups = BTreeMap::new();
+    for entry in entries {
+        if let Some(date) = entry.date {
+            groups.entry((date.year, date.month))
+                .or_insert_with(Vec::new)
+                .push(entry);
+        }
+    }
+    for group in groups.values_mut() {
+        group.sort();
+    }
+    groups
+}
+
+/// Check whether `today` is between the start of the advance warning of an occurrence of the entry and this
+/// occurrence, inclusively.
+pub fn is_active_on(entry: &Entry, today: &Date) -> bool {
+    let last = today.add_days(entry.advance_days as i32).unwrap_or(Date::MAX);
+    !expand(entry, today, &last).is_empty()
+}
+
+/// Get the entries having the tag `tag`, ignoring case.
+pub fn entries_with_tag<'a>(entries: &'a [Entry], tag: &str) -> Vec<&'a Entry> {
+    let tag = tag.to_lowercase();
+    entries.iter()
+        .filter(|entry| entry.tags.iter().any(|entry_tag| entry_tag.to_lowercase() == tag))
+        .collect()
+}
+
+/// Get the pairs of entries whose intervals overlap, including when an entry continues past midnight.
+/// Entries that merely touch, i.e. one ends exactly when the other starts, do not overlap.
+/// An all-day entry without duration lasts for its whole day, like with `Entry::end`.
+/// Recurring entries are ignored: use `expand` to get their occurrences.
+pub fn find_overlaps(entries: &[Entry]) -> Vec<(&Entry, &Entry)> {
+    let mut overlaps = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        for other in &entries[index + 1..] {
+            if let (Some(start), Some(end), Some(other_start), Some(other_end)) =
+                (entry.start(), entry.end(), other.start(), other.end())
+            {
+                if start < other_end && other_start < end {
+                    overlaps.push((entry, other));
+                }
+            }
+        }
+    }
+    overlaps
+}
+
+/// Get the idle durations before the start of each entry scheduled at a time on `date`, except the first, since the
+/// latest end of the entries before it, in chronological order. The gap is zero while an earlier entry goes on.
+pub fn gaps_on(entries: &[Entry], date: &Date) -> Vec<Duration> {
+    let mut day_entries: Vec<_> = entries.iter()
+        .filter(|entry| entry.date == Some(*date) && entry.time.is_some())
+        .collect();
+    day_entries.sort();
+    let mut gaps = vec![];
+    let mut max_end = None;
+    for entry in day_entries {
+        let start = entry.time.map_or(0, |time| time.minutes_since_midnight());
+        if let Some(max_end) = max_end {
+            gaps.push(Duration::from_secs(start.saturating_sub(max_end) as u64 * 60));
+        }
+        let end =
+            match entry.end() {
+                Some((end_date, end)) if end_date == *date => end.minutes_since_midnight(),
+                // The entry continues past midnight, so nothing can start after it on this day.
+                _ => MINUTES_PER_DAY,
+            };
+        max_end = Some(max_end.map_or(end, |max_end: u16| max_end.max(end)));
+    }
+    gaps
+}
+
+/// Get the entries whose message contains `needle`, ignoring case. An empty needle matches every entry.
+pub fn search<'a>(entries: &'a [Entry], needle: &str) -> Vec<&'a Entry> {
+    let needle = needle.to_lowercase();
+    entries.iter()
+        .filter(|entry| entry.msg.to_lowercase().contains(&needle))
+        .collect()
+}
+
+/// Sort the entries from the highest priority to the lowest, entries without priority coming last.
+/// Entries with the same priority are sorted chronologically.
+pub fn sort_by_priority(entries: &mut [Entry]) {
+    entries.sort_by(|entry, other| other.priority.cmp(&entry.priority).then(entry.cmp(other)));
+}
+
+/// Get the earliest entry starting strictly after `time` on `date`, all-day entries starting at midnight.
+/// Recurring entries are ignored: use `expand` to get their occurrences.
+pub fn next_after<'a>(entries: &'a [Entry], date: &Date, time: &Time) -> Option<&'a Entry> {
+    entries.iter()
+        .filter(|entry| entry.start().is_some_and(|start| start > (*date, *time)))
+        .min()
+}
+
+/// Get the sum of the durations of the entries, saturating at `Duration::MAX`.
+pub fn total_duration(entries: &[Entry]) -> Duration {
+    entries.iter()
+        .filter_map(|entry| entry.duration)
+        .fold(Duration::ZERO, Duration::saturating_add)
+}
+
+/// Get the sum of the durations of the entries scheduled on `date`, saturating at `Duration::MAX`.
+pub fn total_duration_on(entries: &[Entry], date: &Date) -> Duration {
+    entries.iter()
+        .filter(|entry| entry.date == Some(*date))
+        .filter_map(|entry| entry.duration)
+        .fold(Duration::ZERO, Duration::saturating_add)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use {Date, Time};
+    use Month::*;
+    use parse_str;
+    use super::{
+        dedup,
+        entries_in_range,
+        entries_on,
+        entries_with_tag,
+        expand,
+        find_overlaps,
+        gaps_on,
+        group_by_month,
+        is_active_on,
+        next_after,
+        search,
+        sort_by_priority,
+        total_duration,
+        total_duration_on,
+    };
+
+    #[test]
+    fn range() {
+        let data = "REM Feb 28 2018 MSG Before
+        REM Mar 1 2018 MSG Start
+        REM Mar 15 2018 MSG Middle
+        REM Mar 31 2018 AT 23:59 MSG End
+        REM Apr 1 2018 MSG After";
+        let entries = parse_str(data).expect("entries");
+        let start = Date { day: 1, month: March, year: 2018 };
+        let end = Date { day: 31, month: March, year: 2018 };
+        let messages: Vec<_> = entries_in_range(&entries, &start, &end).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Start", "Middle", "End"]);
+
+        let end = Date { day: 5, month: April, year: 2018 };
+        assert_eq!(entries_in_range(&entries, &start, &end).len(), 4);
+        assert!(entries_in_range(&entries, &end, &start).is_empty());
+    }
+
+    #[test]
+    fn on_date() {
+        let data = "REM Mar 29 2018 AT 10:00 MSG Yesterday
+        REM Mar 30 2018 AT 19:00 MSG Evening
+        REM Mar 31 2018 AT 08:00 MSG Tomorrow
+        REM Mar 30 2018 AT 08:30 MSG Morning
+        REM Mar 30 2018 AT 12:00 MSG Lunch";
+        let entries = parse_str(data).expect("entries");
+        let date = Date { day: 30, month: March, year: 2018 };
+        let messages: Vec<_> = entries_on(&entries, &date).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Morning", "Lunch", "Evening"]);
+        assert!(entries_on(&entries, &Date { day: 1, month: April, year: 2018 }).is_empty());
+    }
+
+    #[test]
+    fn overlaps() {
+        let data = "REM Mar 30 2018 AT 09:00 DURATION 1:00 MSG First
+        REM Mar 30 2018 AT 09:30 DURATION 1:00 MSG Overlaps first
+        REM Mar 30 2018 AT 10:30 DURATION 1:00 MSG Touches second
+        REM Mar 30 2018 AT 14:00 DURATION 1:00 MSG Alone
+        REM Mar 30 2018 AT 23:30 DURATION 1:00 MSG Late
+        REM Mar 31 2018 AT 00:15 DURATION 0:30 MSG Early";
+        let entries = parse_str(data).expect("entries");
+        let overlaps: Vec<_> = find_overlaps(&entries).iter()
+            .map(|&(entry, other)| (entry.msg.as_str(), other.msg.as_str()))
+            .collect();
+        assert_eq!(overlaps, vec![("First", "Overlaps first"), ("Late", "Early")]);
+    }
+
+    #[test]
+    fn durations() {
+        let data = "REM Mar 30 2018 AT 09:00 DURATION 1:00 MSG First
+        REM Mar 30 2018 AT 11:00 DURATION 0:45 MSG Second
+        REM Mar 30 2018 AT 14:00 MSG Without duration
+        REM Mar 31 2018 AT 10:00 DURATION 2:00 MSG Next day";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(total_duration(&entries), Duration::from_secs((3 * 60 + 45) * 60));
+        let date = Date { day: 30, month: March, year: 2018 };
+        assert_eq!(total_duration_on(&entries, &date), Duration::from_secs(105 * 60));
+        let date = Date { day: 1, month: April, year: 2018 };
+        assert_eq!(total_duration_on(&entries, &date), Duration::ZERO);
+        assert_eq!(total_duration(&[]), Duration::ZERO);
+
+        let data = "REM Mar 30 2018 DURATION 5124095576030431:00 MSG Huge
+        REM Mar 30 2018 DURATION 5124095576030431:00 MSG Other huge";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(total_duration(&entries), Duration::MAX);
+        assert_eq!(total_duration_on(&entries, &Date { day: 30, month: March, year: 2018 }), Duration::MAX);
+    }
+
+    #[test]
+    fn expand_weekly() {
+        let entries = parse_str("REM Mon AT 09:00 MSG Standup\nREM Mar 7 2018 MSG Once").expect("entries");
+        let from = Date { day: 1, month: March, year: 2018 };
+        let to = Date { day: 19, month: March, year: 2018 };
+        let occurrences = expand(&entries[0], &from, &to);
+        let dates: Vec<_> = occurrences.iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![
+            Date { day: 5, month: March, year: 2018 },
+            Date { day: 12, month: March, year: 2018 },
+            Date { day: 19, month: March, year: 2018 },
+        ]);
+        assert!(occurrences.iter().all(|entry| entry.recurrence.is_none() && entry.msg == "Standup"));
+
+        let occurrences = expand(&entries[1], &from, &to);
+        assert_eq!(occurrences.len(), 1);
+        assert_eq!(occurrences[0], entries[1]);
+        assert!(expand(&entries[1], &to, &Date { day: 31, month: March, year: 2018 }).is_empty());
+    }
+
+    #[test]
+    fn expand_monthly() {
+        let entries = parse_str("REM * 31 MSG Pay rent\nREM * 15 MSG Water the plants").expect("entries");
+        let from = Date { day: 1, month: January, year: 2018 };
+        let to = Date { day: 30, month: April, year: 2018 };
+        let dates: Vec<_> = expand(&entries[0], &from, &to).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![
+            Date { day: 31, month: January, year: 2018 },
+            Date { day: 28, month: February, year: 2018 },
+            Date { day: 31, month: March, year: 2018 },
+            Date { day: 30, month: April, year: 2018 },
+        ]);
+
+        let from = Date { day: 1, month: February, year: 2020 };
+        let to = Date { day: 31, month: March, year: 2020 };
+        let dates: Vec<_> = expand(&entries[0], &from, &to).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![
+            Date { day: 29, month: February, year: 2020 },
+            Date { day: 31, month: March, year: 2020 },
+        ]);
+        assert_eq!(expand(&entries[1], &from, &to).len(), 2);
+    }
+
+    #[test]
+    fn expand_until() {
+        let entries = parse_str("REM Mon UNTIL Mar 12 2018 MSG Standup\nREM * 31 UNTIL Mar 30 2018 MSG Pay rent")
+            .expect("entries");
+        let from = Date { day: 1, month: March, year: 2018 };
+        let to = Date { day: 31, month: March, year: 2018 };
+        let dates: Vec<_> = expand(&entries[0], &from, &to).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date { day: 5, month: March, year: 2018 }, Date { day: 12, month: March, year: 2018 }]);
+        assert!(expand(&entries[1], &from, &to).is_empty());
+
+        let to = Date { day: 7, month: March, year: 2018 };
+        assert_eq!(expand(&entries[0], &from, &to).len(), 1);
+    }
+
+    #[test]
+    fn active() {
+        let data = "REM Mar 30 2018 +3 AT 19:00 MSG Event
+        REM Mon +1 MSG Standup
+        REM Mar 30 2018 MSG Today";
+        let entries = parse_str(data).expect("entries");
+        let active = |entry, day, month| is_active_on(entry, &Date { day, month, year: 2018 });
+        assert!(!active(&entries[0], 26, March));
+        assert!(active(&entries[0], 27, March));
+        assert!(active(&entries[0], 30, March));
+        assert!(!active(&entries[0], 31, March));
+
+        assert!(active(&entries[1], 1, April));
+        assert!(active(&entries[1], 2, April));
+        assert!(!active(&entries[1], 3, April));
+
+        assert!(active(&entries[2], 30, March));
+        assert!(!active(&entries[2], 29, March));
+    }
+
+    #[test]
+    fn priority() {
+        let data = "REM Mar 31 2018 MSG Later
+        REM Mar 30 2018 PRIORITY 5 MSG Low
+        REM Mar 30 2018 MSG Sooner
+        REM Apr 1 2018 PRIORITY 10 MSG High
+        REM Mar 29 2018 PRIORITY 10 MSG First high";
+        let mut entries = parse_str(data).expect("entries");
+        sort_by_priority(&mut entries);
+        let messages: Vec<_> = entries.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["First high", "High", "Low", "Sooner", "Later"]);
+    }
+
+    #[test]
+    fn with_tag() {
+        let data = "REM Mar 30 2018 TAG work MSG Meeting
+        REM Mar 30 2018 TAG Personal TAG urgent MSG Dentist
+        REM Mar 31 2018 MSG Untagged
+        REM Apr 1 2018 TAG WORK TAG Urgent MSG Deadline";
+        let entries = parse_str(data).expect("entries");
+        let messages = |tag| -> Vec<_> {
+            entries_with_tag(&entries, tag).iter()
+                .map(|entry| entry.msg.as_str())
+                .collect()
+        };
+        assert_eq!(messages("work"), vec!["Meeting", "Deadline"]);
+        assert_eq!(messages("URGENT"), vec!["Dentist", "Deadline"]);
+        assert_eq!(messages("personal"), vec!["Dentist"]);
+        assert!(messages("holiday").is_empty());
+    }
+
+    #[test]
+    fn search_messages() {
+        let data = "REM Mar 30 2018 MSG Call the Dentist
+        REM Mar 31 2018 MSG DENTIST appointment
+        REM Apr 1 2018 MSG Birthday party
+        REM Apr 2 2018 MSG Été à Paris";
+        let entries = parse_str(data).expect("entries");
+        let messages = |needle| -> Vec<_> {
+            search(&entries, needle).iter()
+                .map(|entry| entry.msg.as_str())
+                .collect()
+        };
+        assert_eq!(messages("dentist"), vec!["Call the Dentist", "DENTIST appointment"]);
+        assert_eq!(messages("PARTY"), vec!["Birthday party"]);
+        assert_eq!(messages("ÉTÉ"), vec!["Été à Paris"]);
+        assert!(messages("meeting").is_empty());
+        assert_eq!(messages("").len(), 4);
+    }
+
+    #[test]
+    fn by_month() {
+        let data = "REM Apr 2 2018 MSG Second of April
+        REM Mar 30 2018 AT 19:00 MSG Evening
+        REM Mon MSG Standup
+        REM Mar 30 2018 AT 08:00 MSG Morning
+        REM Apr 1 2018 MSG First of April
+        REM Mar 2 2019 MSG Next year";
+        let entries = parse_str(data).expect("entries");
+        let groups = group_by_month(&entries);
+        let keys: Vec<_> = groups.keys().cloned().collect();
+        assert_eq!(keys, vec![(2018, March), (2018, April), (2019, March)]);
+        let messages = |key| -> Vec<_> {
+            groups[&key].iter()
+                .map(|entry| entry.msg.as_str())
+                .collect()
+        };
+        assert_eq!(messages((2018, March)), vec!["Morning", "Evening"]);
+        assert_eq!(messages((2018, April)), vec!["First of April", "Second of April"]);
+        assert_eq!(messages((2019, March)), vec!["Next year"]);
+    }
+
+    #[test]
+    fn next() {
+        let data = "REM Mar 30 2018 AT 08:00 MSG Past
+        REM Apr 2 2018 MSG Later
+        REM Mar 30 2018 AT 12:00 MSG Present
+        REM Mon AT 13:00 MSG Standup
+        REM Mar 31 2018 MSG Tomorrow
+        REM Mar 30 2018 AT 12:00:01 MSG Future";
+        let entries = parse_str(data).expect("entries");
+        let date = Date { day: 30, month: March, year: 2018 };
+        let next = next_after(&entries, &date, &Time { hour: 12, minute: 0, second: 0 });
+        assert_eq!(next.map(|entry| entry.msg.as_str()), Some("Future"));
+        let next = next_after(&entries, &date, &Time { hour: 12, minute: 0, second: 1 });
+        assert_eq!(next.map(|entry| entry.msg.as_str()), Some("Tomorrow"));
+        let next = next_after(&entries, &date, &Time { hour: 7, minute: 0, second: 0 });
+        assert_eq!(next.map(|entry| entry.msg.as_str()), Some("Past"));
+        let date = Date { day: 2, month: April, year: 2018 };
+        assert!(next_after(&entries, &date, &Time { hour: 0, minute: 0, second: 0 }).is_none());
+    }
+
+    #[test]
+    fn expand_omit() {
+        let entries = parse_str("REM Mon OMIT Mar 12 2018 OMIT Mar 13 2018 MSG Standup").expect("entries");
+        let from = Date { day: 1, month: March, year: 2018 };
+        let to = Date { day: 19, month: March, year: 2018 };
+        let dates: Vec<_> = expand(&entries[0], &from, &to).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date { day: 5, month: March, year: 2018 }, Date { day: 19, month: March, year: 2018 }]);
+    }
+
+    #[test]
+    fn expand_bounds() {
+        let entries = parse_str("REM Mon MSG Standup
+        REM Sat SKIP WEEKEND MSG Pay
+        REM Dec 31 65535 +3 MSG End").expect("entries");
+        let from = Date { day: 25, month: December, year: 65535 };
+        let dates: Vec<_> = expand(&entries[0], &from, &Date::MAX).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date { day: 30, month: December, year: 65535 }]);
+        let dates: Vec<_> = expand(&entries[1], &from, &Date::MAX).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date { day: 30, month: December, year: 65535 }]);
+
+        let to = Date { day: 10, month: January, year: 0 };
+        let dates: Vec<_> = expand(&entries[1], &Date::MIN, &to).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date { day: 3, month: January, year: 0 }, Date { day: 10, month: January, year: 0 }]);
+
+        assert!(is_active_on(&entries[2], &Date { day: 29, month: December, year: 65535 }));
+        assert!(is_active_on(&entries[2], &Date::MAX));
+        assert!(!is_active_on(&entries[2], &Date { day: 27, month: December, year: 65535 }));
+        let dates: Vec<_> = expand(&entries[2], &Date::MIN, &Date::MAX).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date::MAX]);
+
+        let entries = parse_str("REM Mar 31 2018 SKIP WEEKEND MSG Pay").expect("entries");
+        let from = Date { day: 1, month: April, year: 2018 };
+        let dates: Vec<_> = expand(&entries[0], &from, &Date::MAX).iter()
+            .filter_map(|entry| entry.date)
+            .collect();
+        assert_eq!(dates, vec![Date { day: 2, month: April, year: 2018 }]);
+        assert!(expand(&entries[0], &Date::MIN, &Date { day: 31, month: March, year: 2018 }).is_empty());
+    }
+
+    #[test]
+    fn remove_duplicates() {
+        let data = "REM Mar 30 2018 AT 19:00 MSG Event
+        REM Mar 31 2018 MSG Other
+        REM Mar 30 2018 AT 19:00 MSG Event
+        REM Mar 30 2018 AT 19:00 DURATION 1:00 MSG Event
+        REM Mar 30 2018 AT 19:00 MSG event
+        REM Mar 31 2018 MSG Other";
+        let entries = dedup(parse_str(data).expect("entries"));
+        let lines: Vec<_> = entries.iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(lines, vec![
+            "REM Mar 30 2018 AT 19:00 MSG Event",
+            "REM Mar 31 2018 MSG Other",
+            "REM Mar 30 2018 AT 19:00 DURATION 1:00 MSG Event",
+            "REM Mar 30 2018 AT 19:00 MSG event",
+        ]);
+    }
+
+    #[test]
+    fn gaps() {
+        let data = "REM Mar 30 2018 AT 14:00 DURATION 1:00 MSG Afternoon
+        REM Mar 30 2018 AT 09:00 DURATION 1:30 MSG Morning
+        REM Mar 30 2018 AT 14:30 MSG Overlapping
+        REM Mar 30 2018 MSG All day
+        REM Mar 31 2018 AT 12:00 MSG Other day";
+        let entries = parse_str(data).expect("entries");
+        let date = Date { day: 30, month: March, year: 2018 };
+        assert_eq!(gaps_on(&entries, &date), vec![Duration::from_secs(3 * 60 * 60 + 30 * 60), Duration::from_secs(0)]);
+        assert!(gaps_on(&entries, &Date { day: 31, month: March, year: 2018 }).is_empty());
+
+        let data = "REM Mar 30 2018 AT 09:00 DURATION 3:00 MSG Long
+        REM Mar 30 2018 AT 10:00 DURATION 0:30 MSG Short
+        REM Mar 30 2018 AT 11:00 MSG Covered
+        REM Mar 30 2018 AT 12:15 MSG After";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(gaps_on(&entries, &date), vec![Duration::ZERO, Duration::ZERO, Duration::from_secs(15 * 60)]);
+    }
+}
@@ -0,0 +1,245 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::io::{self, Write};
+use std::iter::FromIterator;
+use std::slice::Iter;
+use std::vec::IntoIter;
+
+use {Date, Entry, Time};
+use query::{entries_in_range, entries_on, search};
+
+/// A set of entries, with the queries available as methods.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Schedule {
+    entries: Vec<Entry>,
+}
+
+impl Schedule {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        Self {
+            entries,
+        }
+    }
+
+    /// Get the entries in progress at `time` on `date`, sorted by start, including the entries started on a previous
+    /// day that continue past midnight. See `Entry::contains_time`.
+    pub fn busy_at(&self, date: &Date, time: &Time) -> Vec<&Entry> {
+        let mut entries: Vec<_> = self.entries.iter()
+            .filter(|entry| entry.date.is_some_and(|entry_date| entry_date <= *date))
+            .filter(|entry| {
+                if entry.date == Some(*date) {
+                    entry.contains_time(time)
+                }
+                else {
+                    // The entry started before the day, so it is in progress until it ends.
+                    entry.end().is_some_and(|end| end > (*date, *time))
+                }
+            })
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<Entry> {
+        self.entries
+    }
+
+    /// Get the entries whose date is between `start` and `end`, inclusive. See `entries_in_range`.
+    pub fn in_range(&self, start: &Date, end: &Date) -> Vec<&Entry> {
+        entries_in_range(&self.entries, start, end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Entry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Get the entries on `date`. See `entries_on`.
+    pub fn on(&self, date: &Date) -> Vec<&Entry> {
+        entries_on(&self.entries, date)
+    }
+
+    /// Remove the entries dated before `cutoff`, returning how many were removed. The recurring entries are kept.
+    pub fn retain_from(&mut self, cutoff: &Date) -> usize {
+        let count = self.entries.len();
+        self.entries.retain(|entry| entry.date.is_none_or(|date| date >= *cutoff));
+        count - self.entries.len()
+    }
+
+    /// Get the entries whose message contains `needle`. See `search`.
+    pub fn search(&self, needle: &str) -> Vec<&Entry> {
+        search(&self.entries, needle)
+    }
+
+    /// Sort the entries chronologically.
+    pub fn sort(&mut self) {
+        self.entries.sort();
+    }
+
+    /// Write the entries in the rem syntax, one per line, in their current order: call `sort` first to write them
+    /// chronologically. The result can be parsed back with `parse`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for entry in &self.entries {
+            writeln!(writer, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Entry>> for Schedule {
+    fn from(entries: Vec<Entry>) -> Self {
+        Self::new(entries)
+    }
+}
+
+impl FromIterator<Entry> for Schedule {
+    fn from_iter<I: IntoIterator<Item = Entry>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Schedule {
+    type Item = Entry;
+    type IntoIter = IntoIter<Entry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Schedule {
+    type Item = &'a Entry;
+    type IntoIter = Iter<'a, Entry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Date, Time};
+    use Month::*;
+    use parse_schedule;
+
+    #[test]
+    fn schedule() {
+        let data = "REM Mar 31 2018 AT 09:00 MSG Meeting
+        REM Mar 30 2018 AT 19:00 MSG Dinner
+        REM Apr 2 2018 MSG Dentist";
+        let mut schedule = parse_schedule(data.as_bytes()).expect("schedule");
+        assert_eq!(schedule.len(), 3);
+        assert!(!schedule.is_empty());
+        schedule.sort();
+        let messages: Vec<_> = schedule.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Dinner", "Meeting", "Dentist"]);
+
+        let date = Date { day: 31, month: March, year: 2018 };
+        assert_eq!(schedule.on(&date)[0].msg, "Meeting");
+        let start = Date { day: 31, month: March, year: 2018 };
+        let end = Date { day: 2, month: April, year: 2018 };
+        assert_eq!(schedule.in_range(&start, &end).len(), 2);
+        assert_eq!(schedule.search("dent")[0].msg, "Dentist");
+
+        let count = (&schedule).into_iter().count();
+        assert_eq!(count, 3);
+        let entries: Vec<_> = schedule.into_iter().collect();
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn retain_from() {
+        let data = "REM Mar 29 2018 MSG Past
+        REM Mar 30 2018 AT 19:00 MSG Cutoff
+        REM Mon MSG Weekly
+        REM Mar 28 2018 MSG Older
+        REM Apr 2 2018 MSG Future";
+        let mut schedule = parse_schedule(data.as_bytes()).expect("schedule");
+        assert_eq!(schedule.retain_from(&Date { day: 30, month: March, year: 2018 }), 2);
+        let messages: Vec<_> = schedule.iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Cutoff", "Weekly", "Future"]);
+    }
+
+    #[test]
+    fn busy_at() {
+        let data = "REM Mar 30 2018 AT 14:00 DURATION 2:00 MSG Workshop
+        REM Mar 30 2018 AT 13:30 DURATION 1:00 MSG Lunch
+        REM Mar 30 2018 AT 15:00 DURATION 1:00 MSG Call
+        REM Mar 29 2018 AT 23:00 DURATION 16:00 MSG Trip
+        REM Mar 29 2018 AT 14:00 DURATION 2:00 MSG Yesterday
+        REM Mar 30 2018 MSG Holiday
+        REM Mar 29 2018 MSG Day before";
+        let schedule = parse_schedule(data.as_bytes()).expect("schedule");
+        let date = Date { day: 30, month: March, year: 2018 };
+        let messages: Vec<_> = schedule.busy_at(&date, &Time { hour: 14, minute: 15, second: 0 }).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Trip", "Holiday", "Lunch", "Workshop"]);
+        let messages: Vec<_> = schedule.busy_at(&date, &Time { hour: 15, minute: 30, second: 0 }).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Holiday", "Workshop", "Call"]);
+        let messages: Vec<_> = schedule.busy_at(&date, &Time { hour: 17, minute: 0, second: 0 }).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Holiday"]);
+        let messages: Vec<_> = schedule.busy_at(&date, &Time { hour: 0, minute: 0, second: 0 }).iter()
+            .map(|entry| entry.msg.as_str())
+            .collect();
+        assert_eq!(messages, vec!["Trip", "Holiday"]);
+        assert!(schedule.busy_at(&Date { day: 31, month: March, year: 2018 }, &Time { hour: 0, minute: 0, second: 0 })
+            .is_empty());
+    }
+
+    #[test]
+    fn write() {
+        let data = "# Comment
+        REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM Mon +2 UNTIL Apr 30 2018 OMIT Apr 2 2018 TAG work MSG Weekly meeting
+
+        REM * 15 PRIORITY 5 MSG \"  Rent  \"
+        REM Mar 31 2018 SKIP WEEKEND MSG Pay
+        REM Apr 1 2018 MSG \"Back\\\\slash\\\\\"
+        REM Mar 30 0018 MSG Antiquity";
+        let schedule = parse_schedule(data.as_bytes()).expect("schedule");
+        let mut output = vec![];
+        schedule.write(&mut output).expect("write");
+        assert_eq!(output.iter().filter(|&&byte| byte == b'\n').count(), 6);
+        let reparsed = parse_schedule(output.as_slice()).expect("schedule");
+        assert_eq!(reparsed, schedule);
+    }
+}
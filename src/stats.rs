@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use {Date, Entry};
+
+/// Statistics about a set of entries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stats {
+    /// The mean duration of the entries having one, or `None` if none has a duration.
+    pub average_duration: Option<Duration>,
+    /// The date with the most entries, the earliest one in case of a tie, or `None` if no entry has a date.
+    pub busiest_day: Option<Date>,
+    pub count: usize,
+    /// The sum of the durations, saturating at `Duration::MAX`.
+    pub total_duration: Duration,
+}
+
+/// Compute statistics about the entries.
+pub fn stats(entries: &[Entry]) -> Stats {
+    let mut counts = BTreeMap::new();
+    let mut durations = 0;
+    let mut total_duration = Duration::ZERO;
+    for entry in entries {
+        if let Some(date) = entry.date {
+            *counts.entry(date).or_insert(0) += 1;
+        }
+        if let Some(duration) = entry.duration {
+            durations += 1;
+            total_duration = total_duration.saturating_add(duration);
+        }
+    }
+    let mut busiest_day = None;
+    let mut max_count = 0;
+    // The dates are visited in order, so only a strictly greater count replaces an earlier date.
+    for (&date, &count) in &counts {
+        if count > max_count {
+            busiest_day = Some(date);
+            max_count = count;
+        }
+    }
+    Stats {
+        average_duration:
+            if durations > 0 {
+                Some(total_duration / durations)
+            }
+            else {
+                None
+            },
+        busiest_day,
+        count: entries.len(),
+        total_duration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use Date;
+    use Month::*;
+    use parse_str;
+    use super::{Stats, stats};
+
+    #[test]
+    fn compute_stats() {
+        let data = "REM Mar 31 2018 AT 09:00 DURATION 1:00 MSG First
+        REM Mar 30 2018 AT 19:00 DURATION 0:30 MSG Second
+        REM Mar 31 2018 MSG Third
+        REM Mar 30 2018 AT 08:00 DURATION 2:00 MSG Fourth
+        REM Mon AT 09:00 MSG Standup
+        REM Apr 1 2018 MSG Fifth";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(stats(&entries), Stats {
+            average_duration: Some(Duration::from_secs(70 * 60)),
+            busiest_day: Some(Date { day: 30, month: March, year: 2018 }),
+            count: 6,
+            total_duration: Duration::from_secs(210 * 60),
+        });
+
+        assert_eq!(stats(&[]), Stats {
+            average_duration: None,
+            busiest_day: None,
+            count: 0,
+            total_duration: Duration::ZERO,
+        });
+
+        let data = "REM Mar 30 2018 DURATION 5124095576030431:00 MSG Huge
+        REM Mar 30 2018 DURATION 5124095576030431:00 MSG Other huge";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(stats(&entries).total_duration, Duration::MAX);
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+use {Entry, format_cells};
+
+const HEADERS: [&str; 4] = ["Date", "Time", "Duration", "Message"];
+
+/// Format the entries as a table with aligned columns for reading in a terminal.
+/// The messages longer than `max_message_width` characters are truncated with an ellipsis.
+pub fn format_table(entries: &[Entry], max_message_width: usize) -> String {
+    let rows: Vec<[String; 4]> = entries.iter()
+        .map(|entry| {
+            let [date, time, duration] = format_cells(entry);
+            [date, time, duration, truncate(&entry.msg, max_message_width)]
+        })
+        .collect();
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut output = String::new();
+    write_row(&mut output, &HEADERS.map(ToString::to_string), &widths);
+    for row in &rows {
+        write_row(&mut output, row, &widths);
+    }
+    output
+}
+
+/// Truncate the text to `width` characters, ending it with an ellipsis if it was too long.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut result: String = text.chars().take(width.saturating_sub(1)).collect();
+    result.push('…');
+    result
+}
+
+fn write_row(output: &mut String, row: &[String; 4], widths: &[usize; 4]) {
+    let mut line = String::new();
+    for (cell, &width) in row.iter().zip(widths) {
+        line.push_str(cell);
+        for _ in cell.chars().count()..width + 2 {
+            line.push(' ');
+        }
+    }
+    output.push_str(line.trim_end());
+    output.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use parse_str;
+    use super::format_table;
+
+    #[test]
+    fn table() {
+        let data = "REM Mar 30 2018 AT 19:00 DURATION 1:15 MSG Event name
+        REM Apr 9 2018 MSG All day
+        REM Mon AT 09:00 DURATION 0:15 MSG A very long message that does not fit";
+        let entries = parse_str(data).expect("entries");
+        assert_eq!(format_table(&entries, 20), "\
+Date         Time   Duration  Message
+Mar 30 2018  19:00  1:15      Event name
+Apr 9 2018                    All day
+Mon          09:00  0:15      A very long message…
+");
+        assert!(format_table(&[], 20).starts_with("Date  Time  Duration  Message\n"));
+    }
+}